@@ -0,0 +1,61 @@
+//! Archive format detection.
+//!
+//! Addons come as pk3s or WADs, but their file names can't be trusted: pk3s
+//! are often renamed to `.zip` or `.kart`, and downloads may have no
+//! extension at all. Instead, the format is read off the first bytes.
+
+use derive_more::{Display, Error};
+
+/// The format of an addon archive.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum ArchiveKind {
+    /// A zip archive, usually a `.pk3`.
+    #[display("pk3")]
+    Pk3,
+    /// A Doom WAD, either an `IWAD` or a `PWAD`.
+    #[display("wad")]
+    Wad,
+}
+
+impl ArchiveKind {
+    /// Detects the format of an archive from its magic bytes.
+    pub fn detect(bytes: &[u8]) -> Result<ArchiveKind, UnsupportedArchive> {
+        match bytes.get(..4) {
+            // a regular zip, or an empty one
+            Some(b"PK\x03\x04" | b"PK\x05\x06") => Ok(ArchiveKind::Pk3),
+            Some(b"IWAD" | b"PWAD") => Ok(ArchiveKind::Wad),
+            Some(magic) => Err(UnsupportedArchive(magic.try_into().expect("4 bytes"))),
+            None => Err(UnsupportedArchive(Default::default())),
+        }
+    }
+}
+
+/// An error for archives that are neither pk3s nor WADs.
+#[derive(Debug, Display, Error)]
+#[display("unsupported file format \"{}\", expected a pk3 or wad", _0.escape_ascii())]
+pub struct UnsupportedArchive(#[error(not(source))] pub [u8; 4]);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(
+            ArchiveKind::detect(b"PK\x03\x04rest").unwrap(),
+            ArchiveKind::Pk3
+        );
+        assert_eq!(
+            ArchiveKind::detect(b"PWAD\0\0\0\0").unwrap(),
+            ArchiveKind::Wad
+        );
+        assert_eq!(ArchiveKind::detect(b"IWAD").unwrap(), ArchiveKind::Wad);
+
+        let err = ArchiveKind::detect(b"\x89PNG\r\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "unsupported file format \"\\x89PNG\", expected a pk3 or wad"
+        );
+        assert!(ArchiveKind::detect(b"PK").is_err());
+    }
+}
@@ -1,12 +1,12 @@
 //! ZDoom patches.
 
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Seek, SeekFrom, Write};
 use std::ops::{Deref, DerefMut};
 
 use bevy_color::color_difference::EuclideanDistance;
 use bevy_color::{Color, Srgba};
 
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 
 use derive_more::{Display, Error};
 
@@ -18,6 +18,13 @@ pub const PALETTE_COLORS: usize = 1 << 8;
 /// The "end-of-column" byte for posts.
 const END_OF_COLUMN: u8 = 0xFF;
 
+/// The largest top delta a post can have before it has to be written
+/// relatively, as a tall patch.
+const MAX_TOP_DELTA: usize = 254;
+
+/// The longest a single post can be.
+const MAX_POST_LENGTH: usize = 254;
+
 /// A patch.
 #[derive(Clone, Debug)]
 pub struct Patch {
@@ -35,7 +42,7 @@ pub struct Patch {
     pub data: Vec<Option<u8>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct PatchHeader {
     width: u16,
     height: u16,
@@ -108,6 +115,114 @@ impl Patch {
             data,
         })
     }
+
+    /// Writes a patch.
+    ///
+    /// Columns taller than 254 pixels are written as a tall patch, so this
+    /// will always read back with [`Patch::read`] as the same patch.
+    pub fn write<W>(&self, mut writer: W) -> Result<(), Error>
+    where
+        W: Write,
+    {
+        let header = PatchHeader {
+            width: self.width,
+            height: self.height,
+            left_offset: i16::try_from(self.left).map_err(|_| Error::Offset(self.left))?,
+            top_offset: i16::try_from(self.top).map_err(|_| Error::Offset(self.top))?,
+        };
+
+        let width = self.width as usize;
+        let height = self.height as usize;
+
+        // encode all columns first, so we know where they are
+        let mut columns = Vec::new();
+        let mut offsets = Vec::with_capacity(width);
+
+        // +8 to skip header
+        let start = width * 4 + 8;
+
+        for x in 0..width {
+            offsets.push(u32::try_from(start + columns.len()).map_err(|_| Error::TooLarge)?);
+
+            // the current y offset of the current post
+            let mut top_delta = 0;
+            let mut y = 0;
+
+            while y < height {
+                if self.data[x + y * width].is_none() {
+                    y += 1;
+                    continue;
+                }
+
+                // find the end of the post
+                let mut length = 0;
+                while y + length < height
+                    && length < MAX_POST_LENGTH
+                    && self.data[x + (y + length) * width].is_some()
+                {
+                    length += 1;
+                }
+
+                write_post_offset(&mut columns, &mut top_delta, y);
+
+                columns.push(length as u8);
+                // buffer byte
+                columns.push(0);
+                columns.extend(
+                    (y..y + length).map(|y| self.data[x + y * width].expect("opaque pixel")),
+                );
+                // buffer byte
+                columns.push(0);
+
+                y += length;
+            }
+
+            columns.push(END_OF_COLUMN);
+        }
+
+        bincode::serialize_into(&mut writer, &header)?;
+        for offset in offsets {
+            bincode::serialize_into(&mut writer, &offset)?;
+        }
+        writer.write_all(&columns)?;
+
+        Ok(())
+    }
+}
+
+/// Writes the offset of the next post, the inverse of [`next_post_offset`].
+///
+/// Posts past [`MAX_TOP_DELTA`] can only be reached relative to the last
+/// post, so empty posts are written to step down the column until it can be
+/// reached.
+fn write_post_offset(buf: &mut Vec<u8>, top_delta: &mut usize, y: usize) {
+    // a post that comes after the last one can be reached absolutely
+    if y <= MAX_TOP_DELTA && (y > *top_delta || y == 0) {
+        buf.push(y as u8);
+        *top_delta = y;
+        return;
+    }
+
+    loop {
+        let max_step = (*top_delta).min(MAX_TOP_DELTA);
+        let offset = y - *top_delta;
+
+        if *top_delta > 0 && offset <= max_step {
+            buf.push(offset as u8);
+            *top_delta = y;
+            return;
+        }
+
+        // write an empty post as a stepping stone
+        if *top_delta < MAX_TOP_DELTA {
+            buf.push(MAX_TOP_DELTA as u8);
+            *top_delta = MAX_TOP_DELTA;
+        } else {
+            buf.push(max_step as u8);
+            *top_delta += max_step;
+        }
+        buf.extend([0, 0, 0]);
+    }
 }
 
 /// Returns `false` if there are no more posts.
@@ -239,11 +354,15 @@ impl Default for Palette {
     }
 }
 
-/// An error for patch reading.
+/// An error for patch reading and writing.
 #[derive(Debug, Display, Error)]
 pub enum Error {
     Deser(bincode::Error),
     Io(std::io::Error),
+    #[display("offset {_0} does not fit in a patch")]
+    Offset(#[error(not(source))] i32),
+    #[display("patch is too large")]
+    TooLarge,
 }
 
 impl From<std::io::Error> for Error {
@@ -262,3 +381,62 @@ impl From<bincode::Error> for Error {
 #[derive(Debug, Display, Error)]
 #[display("invalid palette len: {_0}")]
 pub struct InvalidPaletteLength(#[error(not(source))] pub usize);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::Cursor;
+
+    fn round_trip(patch: &Patch) -> Patch {
+        let mut buf = Vec::new();
+        patch.write(&mut buf).unwrap();
+        Patch::read(Cursor::new(buf)).unwrap()
+    }
+
+    fn assert_patch_eq(a: &Patch, b: &Patch) {
+        assert_eq!(a.left, b.left);
+        assert_eq!(a.top, b.top);
+        assert_eq!(a.width, b.width);
+        assert_eq!(a.height, b.height);
+        assert_eq!(a.data, b.data);
+    }
+
+    #[test]
+    fn test_write() {
+        const SPRAYCAN: &[u8] = include_bytes!("../SPCNK0.lmp");
+
+        let patch = Patch::read(Cursor::new(SPRAYCAN)).unwrap();
+        assert_patch_eq(&patch, &round_trip(&patch));
+    }
+
+    #[test]
+    fn test_write_tall() {
+        let width = 3;
+        let height = 1200;
+
+        let data = (0..width * height)
+            .map(|i| {
+                let (x, y) = (i % width, i / width);
+                match x {
+                    // fully opaque column
+                    0 => Some((y % 256) as u8),
+                    // sparse posts, some only reachable relatively
+                    1 if y % 300 < 3 || y == 1199 => Some(7),
+                    // single post far down the column
+                    2 if (900..1000).contains(&y) => Some(42),
+                    _ => None,
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let patch = Patch {
+            left: -12,
+            top: 300,
+            width: width as u16,
+            height: height as u16,
+            data,
+        };
+        assert_patch_eq(&patch, &round_trip(&patch));
+    }
+}
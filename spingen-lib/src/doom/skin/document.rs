@@ -0,0 +1,317 @@
+//! Editable skin defines.
+//!
+//! [`SkinDefine`] only keeps what `spingen` understands. A [`SkinDocument`]
+//! keeps the lump itself, comments, key order, unknown keys and all, so a
+//! define can be edited and written back without losing anything. Lines that
+//! were not edited are written back exactly as they were read.
+
+use std::fmt::{self, Display, Formatter};
+
+use super::{is_sound, Entry, Error, SkinDefine};
+
+/// An editable skin define.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SkinDocument {
+    bom: bool,
+    lines: Vec<Line>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Line {
+    text: String,
+    /// The line ending, which is empty for the last line if the lump does not
+    /// end with one.
+    ending: String,
+}
+
+impl SkinDocument {
+    /// Parses a skin define.
+    ///
+    /// This never fails, as anything `spingen` does not understand is kept
+    /// as-is. Use [`SkinDocument::define`] to read the define itself.
+    pub fn parse(input: &str) -> SkinDocument {
+        let (bom, input) = match input.strip_prefix('\u{feff}') {
+            Some(input) => (true, input),
+            None => (false, input),
+        };
+
+        let lines = input
+            .split_inclusive('\n')
+            .map(|line| {
+                let text = line
+                    .strip_suffix('\n')
+                    .map(|line| line.strip_suffix('\r').unwrap_or(line))
+                    .unwrap_or(line);
+                Line {
+                    text: text.to_owned(),
+                    ending: line[text.len()..].to_owned(),
+                }
+            })
+            .collect();
+
+        SkinDocument { bom, lines }
+    }
+
+    /// Reads the define of the document.
+    pub fn define(&self) -> Result<SkinDefine, Error> {
+        SkinDefine::read(&self.to_string())
+    }
+
+    /// Gets the value of a key.
+    ///
+    /// Keys are case-insensitive. If a key is set more than once, the last
+    /// one wins, the same as in the game.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.lines
+            .iter()
+            .rev()
+            .filter_map(|line| Entry::parse(&line.text))
+            .find(|entry| entry.key.eq_ignore_ascii_case(key))
+            .map(|entry| entry.value)
+    }
+
+    /// Sets the value of a key.
+    ///
+    /// If the key is already set, only its value is replaced, keeping the
+    /// spacing and quotes around it. Otherwise, the key is added to the end
+    /// of the document. Values with whitespace or `=` in them are quoted, so
+    /// they read back the same.
+    pub fn set(&mut self, key: &str, value: impl Display) {
+        let value = value.to_string();
+
+        let existing = self.lines.iter_mut().rev().find_map(|line| {
+            let entry = Entry::parse(&line.text)?;
+            if !entry.key.eq_ignore_ascii_case(key) {
+                return None;
+            }
+
+            let start = entry.value.as_ptr() as usize - line.text.as_ptr() as usize;
+            let end = start + entry.value.len();
+            Some((line, start..end))
+        });
+
+        match existing {
+            Some((line, range)) => {
+                let quoted = line.text[..range.start].ends_with('"');
+                if !quoted && needs_quotes(&value) {
+                    line.text.replace_range(range, &format!("\"{}\"", value));
+                } else {
+                    line.text.replace_range(range, &value);
+                }
+            }
+            None => {
+                let ending = self.ending();
+                if let Some(last) = self.lines.last_mut() {
+                    if last.ending.is_empty() {
+                        last.ending = ending.clone();
+                    }
+                }
+
+                let text = if needs_quotes(&value) {
+                    format!("{} = \"{}\"", key, value)
+                } else {
+                    format!("{} = {}", key, value)
+                };
+                self.lines.push(Line { text, ending });
+            }
+        }
+    }
+
+    /// Removes every line setting a key.
+    pub fn remove(&mut self, key: &str) {
+        self.lines.retain(|line| {
+            !Entry::parse(&line.text).is_some_and(|entry| entry.key.eq_ignore_ascii_case(key))
+        });
+    }
+
+    /// Writes the fields of a define to the document.
+    ///
+    /// Only fields that are different from the document's own define are
+    /// written, so the rest of the document stays untouched.
+    pub fn apply(&mut self, define: &SkinDefine) {
+        let old = self
+            .define()
+            .map(|define| entries(&define))
+            .unwrap_or_default();
+
+        for (key, value) in entries(define) {
+            let unchanged = old.iter().any(|(old_key, old_value)| {
+                old_key.eq_ignore_ascii_case(&key) && *old_value == value
+            });
+            if unchanged {
+                continue;
+            }
+
+            match value {
+                Some(value) => self.set(&key, value),
+                None => self.remove(&key),
+            }
+        }
+
+        // sounds that are gone from the define
+        for (key, _) in old {
+            let removed = !define
+                .sounds
+                .iter()
+                .any(|sound| sound.sound.eq_ignore_ascii_case(&key));
+            if is_sound(&key) && removed {
+                self.remove(&key);
+            }
+        }
+    }
+
+    /// The line ending the document uses, for new lines.
+    fn ending(&self) -> String {
+        self.lines
+            .iter()
+            .map(|line| &line.ending)
+            .find(|ending| !ending.is_empty())
+            .cloned()
+            .unwrap_or_else(|| "\n".into())
+    }
+}
+
+impl Display for SkinDocument {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.bom {
+            f.write_str("\u{feff}")?;
+        }
+
+        for line in self.lines.iter() {
+            f.write_str(&line.text)?;
+            f.write_str(&line.ending)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl From<&SkinDefine> for SkinDocument {
+    fn from(define: &SkinDefine) -> Self {
+        let mut document = SkinDocument::default();
+        for (key, value) in entries(define) {
+            if let Some(value) = value {
+                document.set(&key, value);
+            }
+        }
+        document
+    }
+}
+
+/// The keys of a define, and their values as written in `S_SKIN`.
+///
+/// `None` means the key should not be written at all.
+fn entries(define: &SkinDefine) -> Vec<(String, Option<String>)> {
+    let flag = |value: bool| value.then(|| "true".to_owned());
+    let list = |value: &[String]| (!value.is_empty()).then(|| value.join(","));
+
+    let mut entries = vec![
+        ("name", Some(define.name.clone())),
+        ("realname", Some(define.realname.clone())),
+        ("prefcolor", Some(define.prefcolor.clone())),
+        ("prefoffcolor", define.prefoffcolor.clone()),
+        ("startcolor", Some(define.startcolor.to_string())),
+        ("kartspeed", Some(define.kartspeed.to_string())),
+        ("kartweight", Some(define.kartweight.to_string())),
+        ("machine", flag(define.flags.machine)),
+        ("ironman", flag(define.flags.ironman)),
+        ("badnik", flag(define.flags.badnik)),
+        ("rivals", list(&define.rivals)),
+        ("facerank", Some(define.facerank.clone())),
+        ("facewant", Some(define.facewant.clone())),
+        ("facemmap", Some(define.facemmap.clone())),
+        ("highresscale", Some(define.highresscale.to_string())),
+        ("followers", list(&define.followers)),
+    ]
+    .into_iter()
+    .map(|(key, value)| (key.to_owned(), value))
+    .collect::<Vec<_>>();
+
+    entries.extend(
+        define
+            .sounds
+            .iter()
+            .map(|sound| (sound.sound.clone(), Some(sound.lump.clone()))),
+    );
+
+    entries
+}
+
+/// Checks if a value has to be quoted to be read back whole.
+fn needs_quotes(value: &str) -> bool {
+    value.contains(|c: char| c == '=' || c.is_whitespace())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\u{feff}# Sakura\r
+name = sakura\r
+realname \"Sakura_Mizuno\"\r
+prefcolor=SKINCOLOR_PINK\r
+\r
+// stats\r
+kartspeed = 9\r
+kartweight = 2\r
+somethingnew = 1\r
+DSKWIN = DSSAKWIN";
+
+    #[test]
+    fn test_round_trip() {
+        let document = SkinDocument::parse(INPUT);
+        assert_eq!(document.to_string(), INPUT);
+
+        let define = document.define().unwrap();
+        assert_eq!(define.kartspeed, 9);
+
+        let mut document = document;
+        document.apply(&define);
+        assert_eq!(document.to_string(), INPUT);
+    }
+
+    #[test]
+    fn test_apply() {
+        let mut document = SkinDocument::parse(INPUT);
+
+        let mut define = document.define().unwrap();
+        define.realname = "Sakura_M".into();
+        define.kartspeed = 3;
+        define.flags.machine = true;
+        define.sounds.clear();
+        document.apply(&define);
+
+        assert_eq!(
+            document.to_string(),
+            "\u{feff}# Sakura\r
+name = sakura\r
+realname \"Sakura_M\"\r
+prefcolor=SKINCOLOR_PINK\r
+\r
+// stats\r
+kartspeed = 3\r
+kartweight = 2\r
+somethingnew = 1\r
+machine = true\r
+"
+        );
+        assert_eq!(document.get("SOMETHINGNEW"), Some("1"));
+    }
+
+    #[test]
+    fn test_set_quotes() {
+        let mut document =
+            SkinDocument::parse("name = sakura\nrealname = Sakura\nprefcolor = Pink\n");
+
+        document.set("realname", "Sakura Haruno");
+        document.set("prefcolor", "Cherry=Pink");
+        assert_eq!(
+            document.to_string(),
+            "name = sakura\nrealname = \"Sakura Haruno\"\nprefcolor = \"Cherry=Pink\"\n"
+        );
+
+        let define = SkinDocument::parse(&document.to_string()).define().unwrap();
+        assert_eq!(define.realname, "Sakura Haruno");
+        assert_eq!(define.prefcolor, "Cherry=Pink");
+    }
+}
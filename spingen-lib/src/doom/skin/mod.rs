@@ -0,0 +1,352 @@
+//! ZDoom skins, and skin definitions.
+
+pub mod document;
+
+use derive_more::{Display, Error};
+
+use super::value::{deserialize, Error as ValueError};
+
+/// A skin definition.
+#[derive(Clone, Debug)]
+pub struct SkinDefine {
+    /// The name that identifies this skin.
+    pub name: String,
+    /// The real, display name of the skin.
+    ///
+    /// Note: In Ring Racers, underscores in this name will be replaced with
+    /// spaces in UI, so `spingen` will replace any underscores with spaces.
+    pub realname: String,
+    /// The start color for spray replacement.
+    pub startcolor: u8,
+    /// The preferred color of the racer.
+    ///
+    /// In `spingen`, it will automatically select this color.
+    pub prefcolor: String,
+    /// The kart speed.
+    ///
+    /// Default is `5`.
+    pub kartspeed: i32,
+    /// The kart weight.
+    ///
+    /// Default is `5`.
+    pub kartweight: i32,
+    /// The preferred color of the racer's opponents, if any.
+    pub prefoffcolor: Option<String>,
+    /// Skin flags.
+    pub flags: SkinFlags,
+    /// The names of the racer's rivals.
+    pub rivals: Vec<String>,
+    /// The graphic for the racer's rank portrait.
+    ///
+    /// Default is `MISSING`.
+    pub facerank: String,
+    /// The graphic for the racer's Wanted portrait.
+    ///
+    /// Default is `MISSING`.
+    pub facewant: String,
+    /// The graphic for the racer's minimap icon.
+    ///
+    /// Default is `MISSING`.
+    pub facemmap: String,
+    /// The scale the sprites are drawn at.
+    ///
+    /// Sprites drawn at a higher resolution use a smaller scale to appear the
+    /// same size in-game. Default is `1.0`.
+    pub highresscale: f32,
+    /// The names of the followers the racer comes with.
+    pub followers: Vec<String>,
+    /// The sounds the racer replaces.
+    pub sounds: Vec<SkinSound>,
+}
+
+/// Skin flags.
+///
+/// Each flag is its own key in `S_SKIN`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SkinFlags {
+    /// The racer is a robot.
+    pub machine: bool,
+    /// The racer picks a new skin every race.
+    pub ironman: bool,
+    /// The racer explodes on death.
+    pub badnik: bool,
+}
+
+/// A sound replaced by a skin.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SkinSound {
+    /// The sound being replaced, like `DSKWIN`.
+    pub sound: String,
+    /// The name of the sound lump to play instead.
+    pub lump: String,
+}
+
+impl SkinDefine {
+    /// Reads a skin define from a lump.
+    pub fn read(input: &str) -> Result<SkinDefine, Error> {
+        let mut name = None::<String>;
+        let mut realname = None::<String>;
+        let mut startcolor = default_startcolor();
+        let mut prefcolor = None::<String>;
+
+        // we are also looking for class information, just if a user might want
+        // to see their class as well.
+        let mut kartspeed = 5;
+        let mut kartweight = 5;
+
+        let mut prefoffcolor = None::<String>;
+        let mut flags = SkinFlags::default();
+        let mut rivals = Vec::new();
+        let mut facerank = default_face();
+        let mut facewant = default_face();
+        let mut facemmap = default_face();
+        let mut highresscale = 1.;
+        let mut followers = Vec::new();
+        let mut sounds = Vec::new();
+
+        // lumps saved on Windows sometimes start with a byte order mark
+        let input = input.strip_prefix('\u{feff}').unwrap_or(input);
+
+        for (line_no, line) in input.lines().enumerate().map(|(no, inner)| (no + 1, inner)) {
+            let Some(Entry {
+                key,
+                value: rest,
+                col,
+            }) = Entry::parse(line)
+            else {
+                continue;
+            };
+
+            if rest.is_empty() {
+                return Err(Error {
+                    kind: ErrorKind::MissingValue(Position { line: line_no, col }),
+                });
+            }
+
+            let parse_err = |err| Error {
+                kind: ErrorKind::InvalidValue(Position { line: line_no, col }, err),
+            };
+
+            if key.eq_ignore_ascii_case("name") {
+                name = Some(deserialize(rest).map_err(parse_err)?);
+            } else if key.eq_ignore_ascii_case("realname") {
+                realname = Some(deserialize(rest).map_err(parse_err)?);
+            } else if key.eq_ignore_ascii_case("prefcolor") {
+                prefcolor = Some(deserialize(rest).map_err(parse_err)?);
+            } else if key.eq_ignore_ascii_case("startcolor") {
+                startcolor = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("kartspeed") {
+                kartspeed = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("kartweight") {
+                kartweight = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("prefoffcolor") {
+                prefoffcolor = Some(deserialize(rest).map_err(parse_err)?);
+            } else if key.eq_ignore_ascii_case("machine") {
+                flags.machine = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("ironman") {
+                flags.ironman = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("badnik") {
+                flags.badnik = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("rivals") {
+                rivals = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("facerank") {
+                facerank = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("facewant") {
+                facewant = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("facemmap") {
+                facemmap = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("highresscale") {
+                highresscale = deserialize(rest).map_err(parse_err)?;
+            } else if key.eq_ignore_ascii_case("followers") {
+                followers = deserialize(rest).map_err(parse_err)?;
+            } else if is_sound(key) {
+                // sound replacements are keyed by the sound's lump name
+                sounds.push(SkinSound {
+                    sound: key.to_ascii_uppercase(),
+                    lump: deserialize(rest).map_err(parse_err)?,
+                });
+            } else {
+                warn!("@ {}:{} unknown key {:?}", line_no, col, key);
+            }
+        }
+
+        Ok(SkinDefine {
+            name: name.ok_or_else(|| Error::missing_field("name"))?,
+            realname: realname.ok_or_else(|| Error::missing_field("realname"))?,
+            prefcolor: prefcolor.ok_or_else(|| Error::missing_field("prefcolor"))?,
+            startcolor,
+            kartspeed,
+            kartweight,
+            prefoffcolor,
+            flags,
+            rivals,
+            facerank,
+            facewant,
+            facemmap,
+            highresscale,
+            followers,
+            sounds,
+        })
+    }
+}
+
+/// A single `key = value` line of a skin define.
+struct Entry<'a> {
+    key: &'a str,
+    value: &'a str,
+    /// The column the value starts at.
+    col: usize,
+}
+
+impl<'a> Entry<'a> {
+    /// Splits a line into its key and value, the same way the game does.
+    ///
+    /// The game treats `=` and whitespace alike as separators, so
+    /// `key = value`, `key=value` and `key value` are all the same. Returns
+    /// `None` for blank lines and `#` or `//` comments.
+    fn parse(line: &'a str) -> Option<Entry<'a>> {
+        let is_separator = |c: char| c == '=' || c.is_whitespace();
+
+        let line = line.trim_end();
+        let content = line.trim_start();
+        if content.is_empty() || content.starts_with('#') || content.starts_with("//") {
+            return None;
+        }
+
+        let key_end = content.find(is_separator).unwrap_or(content.len());
+        let (key, rest) = content.split_at(key_end);
+        let rest = rest.trim_start_matches(is_separator);
+        let col = line[..line.len() - rest.len()].chars().count();
+
+        let value = rest
+            .strip_prefix('"')
+            .and_then(|value| value.strip_suffix('"'))
+            .unwrap_or(rest);
+
+        Some(Entry { key, value, col })
+    }
+}
+
+/// An error for loading a skin define.
+#[derive(Debug, Display, Error)]
+pub struct Error {
+    kind: ErrorKind,
+}
+
+impl Error {
+    fn missing_field(field: &'static str) -> Error {
+        Error {
+            kind: ErrorKind::MissingField(field),
+        }
+    }
+}
+
+/// A position in a file.
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, Hash)]
+#[display("{line}:{col}")]
+pub struct Position {
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Display)]
+pub enum ErrorKind {
+    /// The define is missing a required field.
+    #[display("missing field \"{_0}\"")]
+    MissingField(&'static str),
+    /// A value pailed to parse
+    #[display("@ {_0} {_1}")]
+    InvalidValue(Position, ValueError),
+    /// The define does not have a value.
+    #[display("@ {_0} expected value")]
+    MissingValue(Position),
+}
+
+/// Checks if a key replaces a sound, like `DSKWIN`.
+pub(crate) fn is_sound(key: &str) -> bool {
+    key.len() > 2 && key.get(..2).is_some_and(|p| p.eq_ignore_ascii_case("ds"))
+}
+
+fn default_startcolor() -> u8 {
+    96
+}
+
+fn default_face() -> String {
+    "MISSING".into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read() {
+        const INPUT: &str = "name = sakura
+realname = Sakura_Mizuno
+kartspeed = 3
+kartweight = 2
+startcolor = 160
+prefcolor = SKINCOLOR_PINK
+prefoffcolor = SKINCOLOR_TEA
+machine = true
+rivals = sonic, tails
+facerank = SAKRANK
+facewant = SAKWANT
+highresscale = 0.5
+DSKWIN = DSSAKWIN";
+
+        let skin = SkinDefine::read(INPUT).unwrap();
+
+        assert_eq!(skin.name, "sakura");
+        assert_eq!(skin.kartspeed, 3);
+        assert_eq!(skin.startcolor, 160);
+        assert_eq!(skin.prefoffcolor.as_deref(), Some("SKINCOLOR_TEA"));
+        assert_eq!(
+            skin.flags,
+            SkinFlags {
+                machine: true,
+                ..Default::default()
+            }
+        );
+        assert_eq!(skin.rivals, &["sonic", "tails"]);
+        assert_eq!(skin.facerank, "SAKRANK");
+        assert_eq!(skin.facemmap, "MISSING");
+        assert_eq!(skin.highresscale, 0.5);
+        assert_eq!(
+            skin.sounds,
+            &[SkinSound {
+                sound: "DSKWIN".into(),
+                lump: "DSSAKWIN".into(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_read_tolerant() {
+        const INPUT: &str = "\u{feff}# Sakura's skin\r
+\r
+// stats\r
+name=sakura\r
+  realname \"Sakura_Mizuno\"\r
+prefcolor = SKINCOLOR_PINK   \r
+notakey = 1\r
+ミク = 1\r
+";
+
+        let skin = SkinDefine::read(INPUT).unwrap();
+
+        assert_eq!(skin.name, "sakura");
+        assert_eq!(skin.realname, "Sakura_Mizuno");
+        assert_eq!(skin.prefcolor, "SKINCOLOR_PINK");
+    }
+
+    #[test]
+    fn test_read_missing_value() {
+        let err = SkinDefine::read("name = sakura\nrealname =  ").unwrap_err();
+
+        assert!(matches!(
+            err.kind,
+            ErrorKind::MissingValue(Position { line: 2, col: 10 })
+        ));
+    }
+}
@@ -0,0 +1,1144 @@
+//! The image encoding utilities.
+
+pub mod sheet;
+
+use crate::doom::patch::{Palette, Patch, PALETTE_COLORS};
+use crate::skin::{portrait::PortraitKind, spr2::Spr2, FrameNumber, Skin, SpriteAngle};
+use crate::spray::Spray;
+use crate::Error;
+
+use std::io::{Cursor, Write};
+use std::str::FromStr;
+
+use bevy_color::{Color, ColorToPacked, Srgba};
+
+use derive_more::{Display, From};
+
+use wad::Name;
+
+const SPRAYCAN_GRAPHIC: &[u8] = include_bytes!("../SPCNK0.lmp");
+
+/// An image encoder for a skin.
+#[derive(Debug)]
+pub struct Encoder<'a> {
+    skin_data: &'a Skin,
+    palette: Palette,
+}
+
+impl<'a> Encoder<'a> {
+    /// Creates a new `Encoder`.
+    pub fn new(skin_data: &'a Skin) -> Encoder<'a> {
+        Encoder {
+            skin_data,
+            palette: Palette::default(),
+        }
+    }
+
+    /// Overrides the palette.
+    ///
+    /// By default, the palette is the Ring Racers palette.
+    pub fn with_palette(self, palette: Palette) -> Encoder<'a> {
+        Encoder { palette, ..self }
+    }
+
+    /// Applies a spray to the skin.
+    pub fn with_spray(self, spray: &Spray) -> Encoder<'a> {
+        Encoder {
+            palette: spray.remap(&self.palette, self.skin_data.startcolor.into()),
+            ..self
+        }
+    }
+
+    /// Gets a single sprite of the skin by full, qualified name, and encodes
+    /// it as a still PNG.
+    pub fn sprite<W>(&mut self, writer: W, name: Name) -> Result<(), EncodeError>
+    where
+        W: Write,
+    {
+        self.sprite_with_options(writer, name, GifOptions::default())
+    }
+
+    /// Gets a single sprite of the skin by full, qualified name, and encodes
+    /// it as a still PNG.
+    pub fn sprite_with_options<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        options: GifOptions,
+    ) -> Result<(), EncodeError>
+    where
+        W: Write,
+    {
+        let patch = self.skin_data.read(&name)?;
+        patch_to_image_with_options(writer, &patch, &self.palette, options)
+    }
+
+    /// Gets a sprite index, and encodes it as an image.
+    pub fn sprite_gif<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        frame: FrameNumber,
+    ) -> Result<EncodedImageKind, EncodeError>
+    where
+        W: Write,
+    {
+        self.sprite_gif_with_options(writer, name, frame, GifOptions::default())
+    }
+
+    /// Gets a sprite index, and encodes it as an image.
+    pub fn sprite_gif_with_options<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        frame: FrameNumber,
+        options: GifOptions,
+    ) -> Result<EncodedImageKind, EncodeError>
+    where
+        W: Write,
+    {
+        let name = self.resolve(&name)?;
+
+        // get all patches
+        let mut angles = self.skin_data.iter_angles(&name, frame).collect::<Vec<_>>();
+        if angles.len() == 0 {
+            return Err(Error::NotFound(name.to_string()).into());
+        }
+
+        angles.sort_by_key(|spr2| spr2.index.angle);
+
+        if let [spr2] = angles[..] {
+            // create still png
+            return self
+                .still(writer, spr2, options)
+                .map(|()| EncodedImageKind::Png);
+        }
+
+        // composite every angle onto the same canvas
+        let frames = self.compose(angles)?;
+        self.animation(writer, &frames, options)
+    }
+
+    /// Gets every frame of a sprite at one angle, and encodes it as an image.
+    ///
+    /// Where [`Encoder::sprite_gif`] spins around a single frame, this plays
+    /// the frames back in order, the way the game animates them.
+    pub fn sprite_frames<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        angle: SpriteAngle,
+    ) -> Result<EncodedImageKind, EncodeError>
+    where
+        W: Write,
+    {
+        self.sprite_frames_with_options(writer, name, angle, GifOptions::default())
+    }
+
+    /// Gets every frame of a sprite at one angle, and encodes it as an image.
+    pub fn sprite_frames_with_options<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        angle: SpriteAngle,
+        options: GifOptions,
+    ) -> Result<EncodedImageKind, EncodeError>
+    where
+        W: Write,
+    {
+        let name = self.resolve(&name)?;
+
+        let mut frames = self.skin_data.iter_frames(&name).collect::<Vec<_>>();
+        frames.sort();
+
+        // frames missing the angle are skipped
+        let sprites = frames
+            .into_iter()
+            .filter_map(|frame| self.skin_data.get(&name, frame, angle))
+            .collect::<Vec<_>>();
+
+        match sprites[..] {
+            [] => Err(Error::NotFound(format!("{}?{}", name, angle.into_inner() as char)).into()),
+            [spr2] => self
+                .still(writer, spr2, options)
+                .map(|()| EncodedImageKind::Png),
+            _ => {
+                let frames = self.compose(sprites)?;
+                self.animation(writer, &frames, options)
+            }
+        }
+    }
+
+    /// Gets a single frame of a sprite at one angle, and encodes it as a still
+    /// PNG.
+    ///
+    /// Unlike [`Encoder::sprite`], this goes through the sprite index, so
+    /// missing sprites fall back like they do in-game, and angles only
+    /// available as mirrored graphics are flipped.
+    pub fn sprite_still<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        frame: FrameNumber,
+        angle: SpriteAngle,
+    ) -> Result<(), EncodeError>
+    where
+        W: Write,
+    {
+        self.sprite_still_with_options(writer, name, frame, angle, GifOptions::default())
+    }
+
+    /// Gets a single frame of a sprite at one angle, and encodes it as a still
+    /// PNG.
+    pub fn sprite_still_with_options<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        frame: FrameNumber,
+        angle: SpriteAngle,
+        options: GifOptions,
+    ) -> Result<(), EncodeError>
+    where
+        W: Write,
+    {
+        let name = self.resolve(&name)?;
+
+        let Some(spr2) = self.skin_data.get(&name, frame, angle) else {
+            return Err(Error::NotFound(format!(
+                "{}{}{}",
+                name,
+                frame,
+                angle.into_inner() as char
+            ))
+            .into());
+        };
+
+        self.still(writer, spr2, options)
+    }
+
+    /// Encodes one of the skin's portraits as a still PNG.
+    ///
+    /// Portraits are HUD graphics, so they are never scaled by the skin's
+    /// `highresscale`.
+    pub fn portrait<W>(
+        &mut self,
+        writer: W,
+        kind: PortraitKind,
+        options: GifOptions,
+    ) -> Result<(), EncodeError>
+    where
+        W: Write,
+    {
+        let patch = self.skin_data.portrait(kind)?;
+        patch_to_image_with_options(writer, &patch, &self.palette, options)
+    }
+
+    /// Resolves a sprite through the SPR2 fallback chain.
+    fn resolve(&self, name: &Name) -> Result<Name, EncodeError> {
+        self.skin_data
+            .resolve(name)
+            .map(|resolved| resolved.name)
+            .ok_or_else(|| Error::NotFound(name.to_string()).into())
+    }
+
+    /// Encodes a single sprite as a still PNG, mirroring it if needed.
+    fn still<W>(&self, writer: W, spr2: &Spr2, options: GifOptions) -> Result<(), EncodeError>
+    where
+        W: Write,
+    {
+        let patch = self
+            .compose([spr2])?
+            .pop()
+            .expect("one sprite composes to one patch");
+        patch_to_image_with_options(writer, &patch, &self.palette, self.scaled(options))
+    }
+
+    /// Encodes composited patches in the animation format of the options.
+    fn animation<W>(
+        &self,
+        writer: W,
+        frames: &[Patch],
+        options: GifOptions,
+    ) -> Result<EncodedImageKind, EncodeError>
+    where
+        W: Write,
+    {
+        let options = self.scaled(options);
+
+        match options.format {
+            AnimationFormat::Gif => patches_to_gif(writer, frames, &self.palette, options)
+                .map(|()| EncodedImageKind::Gif),
+            AnimationFormat::Apng => patches_to_apng(writer, frames, &self.palette, options)
+                .map(|()| EncodedImageKind::Apng),
+            AnimationFormat::Webp => patches_to_webp(writer, frames, &self.palette, options)
+                .map(|()| EncodedImageKind::Webp),
+        }
+    }
+
+    /// Applies the skin's `highresscale` to the options.
+    fn scaled(&self, options: GifOptions) -> GifOptions {
+        let highresscale = self.skin_data.highresscale;
+
+        if options.native || highresscale <= 0. {
+            options
+        } else {
+            GifOptions {
+                scale: options.scale * highresscale,
+                ..options
+            }
+        }
+    }
+
+    /// Reads sprites and composites them onto a shared [`Canvas`].
+    ///
+    /// Every returned patch has the same size and offsets, so they can be
+    /// played back as frames without the sprite jittering around.
+    pub fn compose<'b, I>(&self, sprites: I) -> Result<Vec<Patch>, EncodeError>
+    where
+        I: IntoIterator<Item = &'b Spr2>,
+    {
+        let patches = sprites
+            .into_iter()
+            .map(|spr2| {
+                self.skin_data
+                    .read(&spr2.name)
+                    .map(|patch| (patch, spr2.mirror))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut canvases = patches
+            .iter()
+            .map(|(patch, mirror)| Canvas::of(patch, *mirror));
+        let Some(first) = canvases.next() else {
+            return Ok(Vec::new());
+        };
+        let canvas = canvases.try_fold(first, Canvas::union)?;
+
+        Ok(patches
+            .iter()
+            .map(|(patch, mirror)| canvas.draw(patch, *mirror))
+            .collect())
+    }
+}
+
+/// An area that patches are drawn onto, relative to the sprite origin.
+///
+/// Patches are placed the way the game places them: the patch offsets point
+/// from the top left of the patch to the origin. Mirrored patches are flipped
+/// around the origin, too.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Canvas {
+    /// The x of the left edge of the canvas, relative to the origin.
+    pub x: i32,
+    /// The y of the top edge of the canvas, relative to the origin.
+    pub y: i32,
+    /// The width of the canvas.
+    pub width: u16,
+    /// The height of the canvas.
+    pub height: u16,
+}
+
+impl Canvas {
+    /// The area a patch covers when drawn.
+    pub fn of(patch: &Patch, mirror: bool) -> Canvas {
+        let x = if mirror {
+            patch.left - patch.width as i32
+        } else {
+            -patch.left
+        };
+
+        Canvas {
+            x,
+            y: -patch.top,
+            width: patch.width,
+            height: patch.height,
+        }
+    }
+
+    /// The smallest area covering both canvases.
+    ///
+    /// Fails with [`EncodeError::TooLarge`] if the area is too big for a
+    /// patch, like with patches whose offsets are far apart.
+    pub fn union(self, other: Canvas) -> Result<Canvas, EncodeError> {
+        let x = self.x.min(other.x);
+        let y = self.y.min(other.y);
+        let right = (self.x as i64 + self.width as i64).max(other.x as i64 + other.width as i64);
+        let bottom = (self.y as i64 + self.height as i64).max(other.y as i64 + other.height as i64);
+
+        Ok(Canvas {
+            x,
+            y,
+            width: u16::try_from(right - x as i64).map_err(|_| EncodeError::TooLarge)?,
+            height: u16::try_from(bottom - y as i64).map_err(|_| EncodeError::TooLarge)?,
+        })
+    }
+
+    /// Draws a patch onto a new, empty patch the size of the canvas.
+    ///
+    /// The offsets of the new patch point to the origin, so it can be drawn
+    /// like any other patch.
+    pub fn draw(&self, patch: &Patch, mirror: bool) -> Patch {
+        let width = self.width as usize;
+        let mut data = vec![None; width * self.height as usize];
+
+        let area = Canvas::of(patch, mirror);
+        let dest_x = (area.x - self.x) as usize;
+        let dest_y = (area.y - self.y) as usize;
+
+        for (i, color) in patch.data.iter().enumerate() {
+            let Some(color) = color else {
+                continue;
+            };
+
+            let src_x = i % patch.width as usize;
+            let src_y = i / patch.width as usize;
+
+            let src_x = if mirror {
+                patch.width as usize - src_x - 1
+            } else {
+                src_x
+            };
+
+            data[(dest_y + src_y) * width + dest_x + src_x] = Some(*color);
+        }
+
+        Patch {
+            left: -self.x,
+            top: -self.y,
+            width: self.width,
+            height: self.height,
+            data,
+        }
+    }
+}
+
+/// GIF encode options.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GifOptions {
+    /// The factor to upscale by.
+    pub scale: f32,
+    /// The delay between each frame, in centiseconds.
+    ///
+    /// For PNGs, this is discarded.
+    pub delay: u16,
+    /// Whether to mirror across the X axis.
+    pub mirror: bool,
+    /// The format to encode animations in.
+    ///
+    /// For PNGs, this is discarded.
+    pub format: AnimationFormat,
+    /// Whether to draw sprites at the resolution they were drawn at.
+    ///
+    /// By default, sprites are scaled by the skin's `highresscale`, so every
+    /// skin comes out at the same in-game size. This is ignored when encoding
+    /// a sprite by its full name, or sprays.
+    pub native: bool,
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        GifOptions {
+            scale: 1.,
+            delay: 20,
+            mirror: false,
+            format: AnimationFormat::default(),
+            native: false,
+        }
+    }
+}
+
+/// The format of an animation.
+#[derive(Clone, Copy, Debug, Default, Display, PartialEq, Eq, Hash)]
+pub enum AnimationFormat {
+    /// A 256-color GIF.
+    #[default]
+    #[display("gif")]
+    Gif,
+    /// An animated PNG, with full alpha.
+    #[display("apng")]
+    Apng,
+    /// A lossless, animated WebP.
+    #[display("webp")]
+    Webp,
+}
+
+impl FromStr for AnimationFormat {
+    type Err = InvalidAnimationFormat;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("gif") {
+            Ok(AnimationFormat::Gif)
+        } else if s.eq_ignore_ascii_case("apng") {
+            Ok(AnimationFormat::Apng)
+        } else if s.eq_ignore_ascii_case("webp") {
+            Ok(AnimationFormat::Webp)
+        } else {
+            Err(InvalidAnimationFormat(s.to_owned()))
+        }
+    }
+}
+
+/// An error for parsing an [`AnimationFormat`].
+#[derive(Debug, Display, Error)]
+#[display("invalid animation format \"{_0}\"")]
+pub struct InvalidAnimationFormat(#[error(not(source))] pub String);
+
+/// Encodes patches as the frames of a GIF.
+///
+/// All of the patches must be the same size.
+pub fn patches_to_gif<W>(
+    writer: W,
+    patches: &[Patch],
+    palette: &Palette,
+    options: GifOptions,
+) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    let Some(first) = patches.first() else {
+        return Err(EncodeError::NoAngles);
+    };
+
+    let width = (first.width as f32 * options.scale) as u16;
+    let height = (first.height as f32 * options.scale) as u16;
+
+    // begin encoding a gif
+    let mut gif_palette = [0u8; PALETTE_COLORS * 3];
+    for (i, color) in palette.iter().enumerate() {
+        let color_bytes = color.to_srgba().to_u8_array();
+        gif_palette[i * 3..i * 3 + 3].copy_from_slice(&color_bytes[..3]);
+    }
+
+    let mut gif = gif::Encoder::new(writer, width, height, &gif_palette)?;
+    gif.write_extension(gif::ExtensionData::Repetitions(gif::Repeat::Infinite))?;
+
+    for patch in patches {
+        patch_to_gif_frame(&mut gif, patch, options)?;
+    }
+
+    Ok(())
+}
+
+/// Encodes patches as the frames of an animated PNG.
+///
+/// All of the patches must be the same size.
+pub fn patches_to_apng<W>(
+    writer: W,
+    patches: &[Patch],
+    palette: &Palette,
+    options: GifOptions,
+) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    let Some(first) = patches.first() else {
+        return Err(EncodeError::NoAngles);
+    };
+
+    let width = (first.width as f32 * options.scale) as usize;
+    let height = (first.height as f32 * options.scale) as usize;
+
+    let mut encoder = png_encoder(writer, width, height);
+    // loop forever, like the gifs
+    encoder.set_animated(patches.len() as u32, 0)?;
+    // gif delays are in hundredths of a second
+    encoder.set_frame_delay(options.delay, 100)?;
+    encoder.set_dispose_op(png::DisposeOp::Background)?;
+    encoder.set_blend_op(png::BlendOp::Source)?;
+
+    let mut writer = encoder.write_header()?;
+    for patch in patches {
+        writer.write_image_data(&patch_to_rgba(patch, palette, options))?;
+    }
+    writer.finish().map_err(From::from)
+}
+
+fn patch_to_gif_frame<W>(
+    gif: &mut gif::Encoder<W>,
+    patch: &Patch,
+    options: GifOptions,
+) -> Result<(), gif::EncodingError>
+where
+    W: Write,
+{
+    // setup resample heights
+    let width = (patch.width as f32 * options.scale) as usize;
+    let height = (patch.height as f32 * options.scale) as usize;
+
+    // copy patch
+    let mut buf = (0..(width * height)).map(|_| 0).collect::<Vec<u8>>();
+
+    for (i, dest) in buf.iter_mut().enumerate() {
+        // sample by nearest neighbor
+        let dest_x = i % width;
+        let dest_y = i / width;
+
+        let src_x = (dest_x as f32 / options.scale) as usize;
+        let src_y = (dest_y as f32 / options.scale) as usize;
+
+        // saturate
+        //let src_x = min(src_x, patch.width as usize - 1);
+        //let src_y = min(src_y, patch.height as usize - 1);
+
+        let src_x = if options.mirror {
+            patch.width as usize - src_x - 1
+        } else {
+            src_x
+        };
+
+        *dest = patch.data[src_y * patch.width as usize + src_x].unwrap_or(255);
+    }
+
+    gif.write_frame(&gif::Frame {
+        delay: options.delay,
+        dispose: gif::DisposalMethod::Background,
+        transparent: Some(255),
+        buffer: buf.into(),
+        width: width as u16,
+        height: height as u16,
+        ..Default::default()
+    })?;
+
+    Ok(())
+}
+
+/// Renders the spraycan graphic with a spray applied as a still PNG.
+pub fn spray_to_image<W>(writer: W, spray: &Spray) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    let palette = Palette::default();
+    let palette = spray.remap(&palette, 96);
+
+    let patch = Patch::read(Cursor::new(SPRAYCAN_GRAPHIC)).map_err(Error::from)?;
+    patch_to_image(writer, &patch, &palette)
+}
+
+/// Converts a patch to a still PNG.
+pub fn patch_to_image<W>(writer: W, patch: &Patch, palette: &Palette) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    patch_to_image_with_options(writer, patch, palette, GifOptions::default())
+}
+
+/// Converts a patch to a still PNG.
+pub fn patch_to_image_with_options<W>(
+    writer: W,
+    patch: &Patch,
+    palette: &Palette,
+    options: GifOptions,
+) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    // setup resample heights
+    let width = (patch.width as f32 * options.scale) as usize;
+    let height = (patch.height as f32 * options.scale) as usize;
+
+    let data = patch_to_rgba(patch, palette, options);
+
+    let mut writer = png_encoder(writer, width, height).write_header()?;
+    writer.write_image_data(&data)?;
+    writer.finish().map_err(From::from)
+}
+
+/// Encodes patches as the frames of a lossless, animated WebP.
+///
+/// All of the patches must be the same size.
+pub fn patches_to_webp<W>(
+    mut writer: W,
+    patches: &[Patch],
+    palette: &Palette,
+    options: GifOptions,
+) -> Result<(), EncodeError>
+where
+    W: Write,
+{
+    let Some(first) = patches.first() else {
+        return Err(EncodeError::NoAngles);
+    };
+
+    let width = (first.width as f32 * options.scale) as u32;
+    let height = (first.height as f32 * options.scale) as u32;
+
+    if width == 0 || height == 0 {
+        return Err(EncodeError::Empty);
+    }
+
+    let mut chunks = Vec::new();
+
+    // animated, with alpha
+    let mut vp8x = vec![0b0001_0010, 0, 0, 0];
+    vp8x.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+    vp8x.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+    write_riff_chunk(&mut chunks, b"VP8X", &vp8x);
+
+    // transparent background, loop forever
+    write_riff_chunk(&mut chunks, b"ANIM", &[0, 0, 0, 0, 0, 0]);
+
+    // gif delays are in hundredths of a second
+    let duration = options.delay as u32 * 10;
+
+    for patch in patches {
+        let data = patch_to_rgba(patch, palette, options);
+
+        // encode a still image, then pull the image data out of it
+        let mut still = Vec::new();
+        image_webp::WebPEncoder::new(&mut still).encode(
+            &data,
+            width,
+            height,
+            image_webp::ColorType::Rgba8,
+        )?;
+
+        let mut anmf = Vec::new();
+        // the frame covers the whole canvas
+        anmf.extend_from_slice(&[0; 6]);
+        anmf.extend_from_slice(&(width - 1).to_le_bytes()[..3]);
+        anmf.extend_from_slice(&(height - 1).to_le_bytes()[..3]);
+        anmf.extend_from_slice(&duration.to_le_bytes()[..3]);
+        // do not blend, do not dispose
+        anmf.push(0b0000_0010);
+        anmf.extend(frame_data(&still)?);
+        write_riff_chunk(&mut chunks, b"ANMF", &anmf);
+    }
+
+    writer.write_all(b"RIFF")?;
+    writer.write_all(&(chunks.len() as u32 + 4).to_le_bytes())?;
+    writer.write_all(b"WEBP")?;
+    writer.write_all(&chunks)?;
+
+    Ok(())
+}
+
+/// Pulls the `ALPH`, `VP8 ` and `VP8L` chunks out of a still WebP.
+fn frame_data(webp: &[u8]) -> Result<Vec<u8>, EncodeError> {
+    let invalid = || {
+        EncodeError::from(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            "malformed webp",
+        ))
+    };
+
+    let mut data = Vec::new();
+    let mut rest = webp.get(12..).ok_or_else(invalid)?;
+
+    while !rest.is_empty() {
+        let header = rest.get(..8).ok_or_else(invalid)?;
+        let len = u32::from_le_bytes(header[4..8].try_into().expect("4 bytes")) as usize;
+        let padded = 8 + len + len % 2;
+        let chunk = rest.get(..8 + len).ok_or_else(invalid)?;
+
+        if matches!(&header[..4], b"ALPH" | b"VP8 " | b"VP8L") {
+            data.extend_from_slice(chunk);
+            if len % 2 == 1 {
+                data.push(0);
+            }
+        }
+
+        rest = rest.get(padded..).unwrap_or_default();
+    }
+
+    if data.is_empty() {
+        Err(invalid())
+    } else {
+        Ok(data)
+    }
+}
+
+/// Writes a RIFF chunk, padding it to an even length.
+fn write_riff_chunk(buf: &mut Vec<u8>, name: &[u8; 4], data: &[u8]) {
+    buf.extend_from_slice(name);
+    buf.extend_from_slice(&(data.len() as u32).to_le_bytes());
+    buf.extend_from_slice(data);
+    if data.len() % 2 == 1 {
+        buf.push(0);
+    }
+}
+
+/// Converts a patch to 8-bit RGBA pixels, upscaled and mirrored.
+fn patch_to_rgba(patch: &Patch, palette: &Palette, options: GifOptions) -> Vec<u8> {
+    // setup resample heights
+    let width = (patch.width as f32 * options.scale) as usize;
+    let height = (patch.height as f32 * options.scale) as usize;
+
+    let mut data = (0..width * height)
+        .flat_map(|_| {
+            let srgb: Srgba = Color::WHITE.into();
+            srgb.to_u8_array().into_iter()
+        })
+        .collect::<Vec<u8>>();
+
+    for i in 0..width * height {
+        // sample by nearest neighbor
+        let dest_x = i % width;
+        let dest_y = i / width;
+
+        let src_x = (dest_x as f32 / options.scale) as usize;
+        let src_y = (dest_y as f32 / options.scale) as usize;
+
+        // saturate
+        //let src_x = min(src_x, patch.width as usize - 1);
+        //let src_y = min(src_y, patch.height as usize - 1);
+
+        let src_x = if options.mirror {
+            patch.width as usize - src_x - 1
+        } else {
+            src_x
+        };
+
+        let palette_ix = patch.data[src_y * patch.width as usize + src_x];
+        let color_data = &mut data[i * 4..i * 4 + 4];
+
+        if let Some(palette_ix) = palette_ix {
+            palette.copy_color(palette_ix as usize, color_data);
+        } else {
+            // encode transparent pixel
+            palette.copy_color(255, color_data);
+            color_data[3] = 0;
+        }
+    }
+
+    data
+}
+
+/// Creates a PNG encoder for RGBA data.
+fn png_encoder<W>(writer: W, width: usize, height: usize) -> png::Encoder<'static, W>
+where
+    W: Write,
+{
+    let mut encoder = png::Encoder::new(writer, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgba);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_source_gamma(png::ScaledFloat::new(1.0 / 2.2)); // 1.0 / 2.2, unscaled, but rounded
+    let source_chromaticities = png::SourceChromaticities::new(
+        // Using unscaled instantiation here
+        (0.31270, 0.32900),
+        (0.64000, 0.33000),
+        (0.30000, 0.60000),
+        (0.15000, 0.06000),
+    );
+    encoder.set_source_chromaticities(source_chromaticities);
+    encoder
+}
+
+/// The type of image that has been encoded.
+///
+/// The image encoder may choose to encode an image as a PNG if an animation
+/// would only consist of one frame.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum EncodedImageKind {
+    #[default]
+    Png,
+    Gif,
+    Apng,
+    Webp,
+}
+
+impl EncodedImageKind {
+    /// Represents the `EncodedImageKind` as a MIME type.
+    pub fn as_mime_type(&self) -> &'static str {
+        match self {
+            EncodedImageKind::Png => "image/png",
+            EncodedImageKind::Gif => "image/gif",
+            EncodedImageKind::Apng => "image/apng",
+            EncodedImageKind::Webp => "image/webp",
+        }
+    }
+
+    /// The file extension of the `EncodedImageKind`.
+    pub fn as_extension(&self) -> &'static str {
+        match self {
+            EncodedImageKind::Png | EncodedImageKind::Apng => "png",
+            EncodedImageKind::Gif => "gif",
+            EncodedImageKind::Webp => "webp",
+        }
+    }
+}
+
+/// An error for encoding.
+#[derive(Debug, Display, Error, From)]
+pub enum EncodeError {
+    Loader(Error),
+    Gif(gif::EncodingError),
+    Png(png::EncodingError),
+    Webp(image_webp::EncodingError),
+    Io(std::io::Error),
+    #[display("no angles to make gif")]
+    NoAngles,
+    #[display("image is empty at this scale")]
+    Empty,
+    #[display("image is too large")]
+    TooLarge,
+}
+
+impl EncodeError {
+    pub fn not_found(&self) -> bool {
+        if let EncodeError::Loader(err) = self {
+            err.not_found()
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use crate::skin::loaders::Pk3SkinLoader;
+
+    /// Builds a skin out of its `S_SKIN` and lumps.
+    pub fn skin(s_skin: &str, lumps: &[(&str, &[u8])]) -> Skin {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+
+        zip.start_file("skins/test/S_SKIN", options).unwrap();
+        zip.write_all(s_skin.as_bytes()).unwrap();
+        for (name, data) in lumps {
+            zip.start_file(format!("skins/test/{}.lmp", name), options)
+                .unwrap();
+            zip.write_all(data).unwrap();
+        }
+
+        let bytes = zip.finish().unwrap().into_inner();
+        Pk3SkinLoader::new(bytes).unwrap().next().unwrap().unwrap()
+    }
+
+    /// Builds a 1x1 patch lump of a single color.
+    pub fn pixel(color: u8) -> Vec<u8> {
+        let mut lump = vec![1, 0, 1, 0, 0, 0, 0, 0];
+        // the only column starts right after its offset
+        lump.extend_from_slice(&12u32.to_le_bytes());
+        lump.extend_from_slice(&[0, 1, 0, color, 0, 0xFF]);
+        lump
+    }
+
+    #[test]
+    fn test_highresscale() {
+        let skin = skin(
+            "name = test\nrealname = Test\nprefcolor = Pink\nhighresscale = 0.5\n",
+            &[("STINA0", SPRAYCAN_GRAPHIC)],
+        );
+
+        let size = |native: bool| {
+            let mut buf = Vec::new();
+            let options = GifOptions {
+                scale: 2.,
+                native,
+                ..Default::default()
+            };
+            let kind = Encoder::new(&skin)
+                .sprite_gif_with_options(&mut buf, "STIN".parse().unwrap(), FrameNumber::A, options)
+                .unwrap();
+            assert!(matches!(kind, EncodedImageKind::Png));
+
+            let reader = png::Decoder::new(Cursor::new(buf)).read_info().unwrap();
+            (reader.info().width, reader.info().height)
+        };
+
+        // the spraycan is 56x80
+        assert_eq!(size(false), (56, 80));
+        assert_eq!(size(true), (112, 160));
+    }
+
+    #[test]
+    fn test_sprite_frames() {
+        let (a, b, c, d) = (pixel(1), pixel(2), pixel(3), pixel(4));
+        // frame B is missing the forward angle, and frame C has all angles
+        let skin = skin(
+            "name = test\nrealname = Test\nprefcolor = Pink\n",
+            &[
+                ("STIND1", &d),
+                ("STINB2", &b),
+                ("STINA1", &a),
+                ("STINC0", &c),
+            ],
+        );
+
+        let mut buf = Vec::new();
+        let options = GifOptions {
+            format: AnimationFormat::Apng,
+            ..Default::default()
+        };
+        let kind = Encoder::new(&skin)
+            .sprite_frames_with_options(
+                &mut buf,
+                "STIN".parse().unwrap(),
+                SpriteAngle::FORWARD,
+                options,
+            )
+            .unwrap();
+        assert!(matches!(kind, EncodedImageKind::Apng));
+
+        let mut reader = png::Decoder::new(Cursor::new(buf)).read_info().unwrap();
+        let num_frames = reader.info().animation_control.unwrap().num_frames;
+
+        let mut colors = Vec::new();
+        for _ in 0..num_frames {
+            let mut frame = vec![0; reader.output_buffer_size()];
+            reader.next_frame(&mut frame).unwrap();
+            colors.push(frame[..3].to_vec());
+        }
+
+        let palette = Palette::default();
+        let expected = [1, 3, 4]
+            .map(|ix| {
+                let mut color = vec![0; 3];
+                palette.copy_color(ix, &mut color);
+                color
+            })
+            .to_vec();
+        assert_eq!(colors, expected);
+    }
+
+    #[test]
+    fn test_portrait() {
+        let rank = pixel(96);
+        let skin = skin(
+            "name = test\nrealname = Test\nprefcolor = Pink\nfacerank = TESTRANK\nhighresscale = 0.5\n",
+            &[("TESTRANK", &rank)],
+        );
+        let spray = crate::spray::sprays()
+            .into_iter()
+            .find(|spray| spray.id.eq_ignore_ascii_case("SKINCOLOR_RED"))
+            .unwrap();
+
+        let mut buf = Vec::new();
+        let options = GifOptions {
+            scale: 2.,
+            ..Default::default()
+        };
+        Encoder::new(&skin)
+            .with_spray(&spray)
+            .portrait(&mut buf, PortraitKind::Rank, options)
+            .unwrap();
+
+        let mut reader = png::Decoder::new(Cursor::new(buf)).read_info().unwrap();
+        // portraits ignore highresscale
+        assert_eq!((reader.info().width, reader.info().height), (2, 2));
+
+        let mut image = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut image).unwrap();
+
+        let mut plain = vec![0; 3];
+        Palette::default().copy_color(96, &mut plain);
+        let mut sprayed = vec![0; 3];
+        spray
+            .remap(&Palette::default(), 96)
+            .copy_color(96, &mut sprayed);
+
+        assert_ne!(plain, sprayed);
+        assert_eq!(&image[..3], &sprayed[..]);
+    }
+
+    #[test]
+    fn test_canvas() {
+        // a 2x1 patch with its origin on its right pixel
+        let a = Patch {
+            left: 1,
+            top: 1,
+            width: 2,
+            height: 1,
+            data: vec![Some(1), Some(2)],
+        };
+        // a 1x2 patch with its origin below it
+        let b = Patch {
+            left: 0,
+            top: 2,
+            width: 1,
+            height: 2,
+            data: vec![Some(3), Some(4)],
+        };
+
+        let canvas = Canvas::of(&a, false).union(Canvas::of(&b, false)).unwrap();
+        assert_eq!(
+            canvas,
+            Canvas {
+                x: -1,
+                y: -2,
+                width: 2,
+                height: 2
+            }
+        );
+
+        let frame = canvas.draw(&a, false);
+        assert_eq!((frame.left, frame.top), (1, 2));
+        assert_eq!(frame.data, &[None, None, Some(1), Some(2)]);
+
+        let frame = canvas.draw(&b, false);
+        assert_eq!(frame.data, &[None, Some(3), None, Some(4)]);
+
+        // mirrored patches flip around the origin
+        let canvas = Canvas::of(&a, true).union(Canvas::of(&b, false)).unwrap();
+        assert_eq!((canvas.x, canvas.width), (-1, 2));
+
+        let frame = canvas.draw(&a, true);
+        assert_eq!(frame.data, &[None, None, Some(2), Some(1)]);
+
+        // offsets too far apart to fit in one patch
+        let far = Patch { left: -70000, ..b };
+        assert!(matches!(
+            Canvas::of(&a, false).union(Canvas::of(&far, false)),
+            Err(EncodeError::TooLarge)
+        ));
+    }
+
+    #[test]
+    fn test_apng() {
+        let patches = [Some(1), None].map(|color| Patch {
+            left: 0,
+            top: 0,
+            width: 1,
+            height: 1,
+            data: vec![color],
+        });
+
+        let mut buf = Vec::new();
+        let options = GifOptions {
+            scale: 2.,
+            ..Default::default()
+        };
+        patches_to_apng(&mut buf, &patches, &Palette::default(), options).unwrap();
+
+        let reader = png::Decoder::new(Cursor::new(buf)).read_info().unwrap();
+        let info = reader.info();
+        assert_eq!((info.width, info.height), (2, 2));
+        assert_eq!(info.animation_control.map(|ac| ac.num_frames), Some(2));
+    }
+
+    #[test]
+    fn test_webp() {
+        let patches = [Some(1), None, Some(2)].map(|color| Patch {
+            left: 0,
+            top: 0,
+            width: 3,
+            height: 1,
+            data: vec![color; 3],
+        });
+
+        let mut buf = Vec::new();
+        patches_to_webp(
+            &mut buf,
+            &patches,
+            &Palette::default(),
+            GifOptions::default(),
+        )
+        .unwrap();
+
+        let decoder = image_webp::WebPDecoder::new(Cursor::new(buf)).unwrap();
+        assert!(decoder.is_animated());
+        assert_eq!(decoder.num_frames(), 3);
+        assert_eq!(decoder.dimensions(), (3, 1));
+
+        let options = GifOptions {
+            scale: 0.1,
+            ..Default::default()
+        };
+        let err = patches_to_webp(Vec::new(), &patches, &Palette::default(), options);
+        assert!(matches!(err, Err(EncodeError::Empty)));
+    }
+}
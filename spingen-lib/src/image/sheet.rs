@@ -0,0 +1,391 @@
+//! Sprite sheet export.
+//!
+//! A sprite sheet packs every angle and frame of a sprite into a single PNG,
+//! with a [`SpriteSheet`] descriptor saying where each cell is. The
+//! descriptor serializes to JSON, so the sheet can be animated on a canvas or
+//! with CSS without calling back into `spingen` for every frame.
+
+use std::io::Write;
+
+use serde::Serialize;
+
+use wad::Name;
+
+use crate::doom::patch::Patch;
+
+use super::{patch_to_image_with_options, Canvas, EncodeError, Encoder, GifOptions};
+
+/// The layout of a sprite sheet.
+///
+/// All measurements are in pixels of the encoded image, so they are already
+/// multiplied by [`GifOptions::scale`], and the skin's `highresscale` unless
+/// [`GifOptions::native`] is set.
+#[derive(Clone, Debug, Default, PartialEq, Serialize)]
+pub struct SpriteSheet {
+    /// The width of the sheet.
+    pub width: u32,
+    /// The height of the sheet.
+    pub height: u32,
+    /// Every cell of the sheet, ordered by sprite, then frame, then angle.
+    pub cells: Vec<SheetCell>,
+}
+
+/// A single cell of a [`SpriteSheet`].
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SheetCell {
+    /// The 4-character SPR2 name.
+    pub sprite: String,
+    /// The name of the graphic the cell was drawn from.
+    pub name: String,
+    /// The frame character.
+    pub frame: char,
+    /// The angle character.
+    pub angle: char,
+    /// Whether the graphic was mirrored to produce this cell.
+    pub mirror: bool,
+    /// Whether the angle is missing from the skin, and was filled in by
+    /// mirroring the opposite angle.
+    pub synthesised: bool,
+    /// The x of the left edge of the cell.
+    pub x: u32,
+    /// The y of the top edge of the cell.
+    pub y: u32,
+    /// The width of the cell.
+    pub width: u32,
+    /// The height of the cell.
+    pub height: u32,
+    /// The distance from the left edge of the cell to the sprite origin.
+    pub left: i32,
+    /// The distance from the top edge of the cell to the sprite origin.
+    pub top: i32,
+}
+
+impl Encoder<'_> {
+    /// Encodes every frame and angle of a sprite as a PNG sprite sheet.
+    ///
+    /// Each frame gets its own row, with the angles laid out left to right.
+    pub fn sprite_sheet<W>(
+        &mut self,
+        writer: W,
+        name: Name,
+        options: GifOptions,
+    ) -> Result<SpriteSheet, EncodeError>
+    where
+        W: Write,
+    {
+        let name = self.resolve(&name)?;
+        self.sheet(writer, &[name], options)
+    }
+
+    /// Encodes every sprite of the skin as a PNG sprite sheet.
+    ///
+    /// Like [`Encoder::sprite_sheet`], but with the rows of every sprite
+    /// stacked on top of each other, in the order of
+    /// [`Skin::sprites`](crate::skin::Skin::sprites).
+    pub fn skin_sheet<W>(
+        &mut self,
+        writer: W,
+        options: GifOptions,
+    ) -> Result<SpriteSheet, EncodeError>
+    where
+        W: Write,
+    {
+        let names = self.skin_data.sprites();
+        self.sheet(writer, &names, options)
+    }
+
+    fn sheet<W>(
+        &self,
+        writer: W,
+        names: &[Name],
+        options: GifOptions,
+    ) -> Result<SpriteSheet, EncodeError>
+    where
+        W: Write,
+    {
+        // scale every cell on its own, so the cells stay whole pixels
+        let options = self.scaled(options);
+        let mut rows = Vec::new();
+
+        for name in names {
+            let mut frames = self.skin_data.iter_frames(name).collect::<Vec<_>>();
+            frames.sort();
+
+            for frame in frames {
+                let mut angles = self.skin_data.iter_angles(name, frame).collect::<Vec<_>>();
+                angles.sort_by_key(|spr2| spr2.index.angle);
+
+                let row = angles
+                    .into_iter()
+                    .map(|spr2| {
+                        let patch = self.skin_data.read(&spr2.name)?;
+                        // drawing onto its own canvas takes care of mirroring
+                        let mirror = spr2.mirror ^ options.mirror;
+                        let cell = Canvas::of(&patch, mirror).draw(&patch, mirror);
+                        Ok(((spr2, mirror), resample(&cell, options.scale)?))
+                    })
+                    .collect::<Result<Vec<_>, EncodeError>>()?;
+                rows.push(row);
+            }
+        }
+
+        let (sheet, layout) = pack(&rows)?;
+
+        let options = GifOptions {
+            scale: 1.,
+            mirror: false,
+            ..options
+        };
+        patch_to_image_with_options(writer, &sheet, &self.palette, options)?;
+
+        let cells = layout
+            .into_iter()
+            .map(|((spr2, mirror), x, y, cell)| SheetCell {
+                sprite: spr2.index.name.to_string(),
+                name: spr2.name.to_string(),
+                frame: spr2.index.frame.to_ascii_char() as char,
+                angle: spr2.index.angle.into_inner() as char,
+                mirror,
+                synthesised: spr2.synthesised,
+                x,
+                y,
+                width: cell.width as u32,
+                height: cell.height as u32,
+                left: cell.left,
+                top: cell.top,
+            })
+            .collect();
+
+        Ok(SpriteSheet {
+            width: sheet.width as u32,
+            height: sheet.height as u32,
+            cells,
+        })
+    }
+}
+
+/// Resamples a patch by nearest neighbor, like the encoders do.
+fn resample(patch: &Patch, scale: f32) -> Result<Patch, EncodeError> {
+    let size = |n: u16| {
+        let n = n as f32 * scale;
+        if n <= u16::MAX as f32 {
+            Ok(n as u16)
+        } else {
+            Err(EncodeError::TooLarge)
+        }
+    };
+    let width = size(patch.width)?;
+    let height = size(patch.height)?;
+
+    let data = (0..width as usize * height as usize)
+        .map(|i| {
+            let src_x = ((i % width as usize) as f32 / scale) as usize;
+            let src_y = ((i / width as usize) as f32 / scale) as usize;
+            patch.data[src_y * patch.width as usize + src_x]
+        })
+        .collect();
+
+    Ok(Patch {
+        left: (patch.left as f32 * scale).round() as i32,
+        top: (patch.top as f32 * scale).round() as i32,
+        width,
+        height,
+        data,
+    })
+}
+
+/// Where each cell of a sheet was placed.
+type Layout<'a, K> = Vec<(K, u32, u32, &'a Patch)>;
+
+/// Lays out rows of cells onto a single patch.
+///
+/// Returns the sheet, and where each cell was placed, or
+/// [`EncodeError::TooLarge`] if the sheet doesn't fit in a patch.
+fn pack<K: Copy>(rows: &[Vec<(K, Patch)>]) -> Result<(Patch, Layout<'_, K>), EncodeError> {
+    let mut layout = Vec::new();
+    let mut width = 0u32;
+    let mut height = 0u32;
+
+    for row in rows {
+        let mut x = 0u32;
+        let mut row_height = 0u32;
+
+        for (key, cell) in row {
+            layout.push((*key, x, height, cell));
+            x = x
+                .checked_add(cell.width as u32)
+                .ok_or(EncodeError::TooLarge)?;
+            row_height = row_height.max(cell.height as u32);
+        }
+
+        width = width.max(x);
+        height = height
+            .checked_add(row_height)
+            .ok_or(EncodeError::TooLarge)?;
+    }
+
+    let width = u16::try_from(width).map_err(|_| EncodeError::TooLarge)?;
+    let height = u16::try_from(height).map_err(|_| EncodeError::TooLarge)?;
+
+    let mut data = vec![None; width as usize * height as usize];
+
+    for (_, x, y, cell) in layout.iter() {
+        for (i, color) in cell.data.iter().enumerate() {
+            let cell_x = i % cell.width as usize;
+            let cell_y = i / cell.width as usize;
+
+            data[(*y as usize + cell_y) * width as usize + *x as usize + cell_x] = *color;
+        }
+    }
+
+    let sheet = Patch {
+        left: 0,
+        top: 0,
+        width,
+        height,
+        data,
+    };
+
+    Ok((sheet, layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::skin::spr2::{Spr2, SpriteIndex};
+    use crate::skin::{FrameNumber, SpriteAngle};
+
+    #[test]
+    fn test_pack() {
+        let name = "STINA1".parse::<Name>().unwrap();
+        let spr2 = Spr2::new(
+            SpriteIndex::new(
+                "STIN".parse().unwrap(),
+                FrameNumber::A,
+                SpriteAngle::FORWARD,
+            ),
+            name,
+            false,
+        );
+
+        let a = Patch {
+            left: 0,
+            top: 0,
+            width: 2,
+            height: 1,
+            data: vec![Some(1), Some(2)],
+        };
+        let b = Patch {
+            left: 0,
+            top: 0,
+            width: 1,
+            height: 2,
+            data: vec![Some(3), Some(4)],
+        };
+
+        let rows = vec![
+            vec![(&spr2, a.clone()), (&spr2, b.clone())],
+            vec![(&spr2, a)],
+        ];
+        let (sheet, layout) = pack(&rows).unwrap();
+
+        assert_eq!((sheet.width, sheet.height), (3, 3));
+        assert_eq!(
+            layout
+                .iter()
+                .map(|(_, x, y, _)| (*x, *y))
+                .collect::<Vec<_>>(),
+            &[(0, 0), (2, 0), (0, 2)]
+        );
+        assert_eq!(
+            sheet.data,
+            &[
+                Some(1),
+                Some(2),
+                Some(3),
+                None,
+                None,
+                Some(4),
+                Some(1),
+                Some(2),
+                None
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sheet_highresscale() {
+        let graphic = include_bytes!("../SPCNK0.lmp");
+        let skin = crate::image::tests::skin(
+            "name = test\nrealname = Test\nprefcolor = Pink\nhighresscale = 0.5\n",
+            &[
+                ("STINA1", graphic),
+                ("STINA2A8", graphic),
+                ("STINB1", graphic),
+            ],
+        );
+
+        let sheet = |scale: f32, native: bool| {
+            let mut buf = Vec::new();
+            let options = GifOptions {
+                scale,
+                native,
+                ..Default::default()
+            };
+            let sheet = Encoder::new(&skin)
+                .sprite_sheet(&mut buf, "STIN".parse().unwrap(), options)
+                .unwrap();
+
+            // the layout has to match the pixels
+            let reader = png::Decoder::new(std::io::Cursor::new(buf))
+                .read_info()
+                .unwrap();
+            assert_eq!(
+                (reader.info().width, reader.info().height),
+                (sheet.width, sheet.height)
+            );
+            for cell in sheet.cells.iter() {
+                assert!(cell.x + cell.width <= sheet.width);
+                assert!(cell.y + cell.height <= sheet.height);
+            }
+
+            sheet
+        };
+
+        let native = sheet(1., true);
+        assert_eq!(native.cells[0].width, 56);
+        assert_eq!(native.cells[1].x, 56);
+
+        // 56x80 at a scale of 0.3 is 16.8x24, so each cell is 16 pixels wide
+        let scaled = sheet(0.6, false);
+        assert_eq!(scaled.cells.len(), native.cells.len());
+        assert_eq!((scaled.cells[0].width, scaled.cells[0].height), (16, 24));
+        assert_eq!(scaled.cells[1].x, 16);
+        assert_eq!(scaled.width, native.width / 56 * 16);
+    }
+
+    #[test]
+    fn test_pack_too_large() {
+        let cell = |width: u16, height: u16| Patch {
+            left: 0,
+            top: 0,
+            width,
+            height,
+            data: vec![None; width as usize * height as usize],
+        };
+
+        // two cells side by side are wider than a patch can be
+        let rows = vec![vec![((), cell(40000, 1)), ((), cell(40000, 1))]];
+        assert!(matches!(pack(&rows), Err(EncodeError::TooLarge)));
+
+        // and so are two rows on top of each other
+        let rows = vec![vec![((), cell(1, 40000))], vec![((), cell(1, 40000))]];
+        assert!(matches!(pack(&rows), Err(EncodeError::TooLarge)));
+
+        assert!(matches!(
+            resample(&cell(56, 80), 2000.),
+            Err(EncodeError::TooLarge)
+        ));
+    }
+}
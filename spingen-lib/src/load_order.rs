@@ -0,0 +1,425 @@
+//! Ordered addon loading.
+//!
+//! Players load addons as a stack: a color pack first, then the character
+//! packs whose `prefcolor` refers to it. Like in the game, a skin or spray
+//! loaded later replaces any earlier one with the same name, and references
+//! between addons are only resolved once everything is loaded.
+
+use std::path::Path;
+
+use bytes::Bytes;
+
+use derive_more::Display;
+
+use eyre::Report;
+
+use crate::archive::ArchiveKind;
+use crate::skin::{
+    loaders::{DirSkinLoader, Pk3SkinLoader, WadSkinLoader},
+    Skin,
+};
+use crate::spray::{
+    loaders::{DirSprayLoader, Pk3SprayLoader, WadSprayLoader},
+    sprays, Spray,
+};
+
+/// An ordered list of loaded addons.
+///
+/// The sprays of the base game always come first.
+#[derive(Clone, Debug)]
+pub struct LoadOrder {
+    base: Vec<Spray>,
+    files: Vec<AddonFile>,
+}
+
+/// Everything loaded from a single addon.
+#[derive(Clone, Debug)]
+pub struct AddonFile {
+    /// The name of the addon, usually its file name.
+    pub name: String,
+    /// The skins of the addon, in the order they were found.
+    pub skins: Vec<Skin>,
+    /// The sprays of the addon.
+    pub sprays: Vec<Spray>,
+}
+
+impl AddonFile {
+    /// Reads an addon archive.
+    ///
+    /// The format is detected from the contents. Skins and sprays that fail to
+    /// load are logged and skipped, so one broken lump doesn't lose the whole
+    /// addon.
+    pub fn read(name: impl Into<String>, bytes: impl Into<Bytes>) -> Result<AddonFile, Report> {
+        let name = name.into();
+        let bytes = bytes.into();
+
+        let kind = ArchiveKind::detect(&bytes)?;
+        let (sprays, skins) = match kind {
+            ArchiveKind::Pk3 => (
+                collect(Pk3SprayLoader::new(bytes.clone())?, "spray"),
+                collect(Pk3SkinLoader::new(bytes)?, "skin"),
+            ),
+            ArchiveKind::Wad => (
+                collect(WadSprayLoader::new(bytes.clone())?, "spray"),
+                collect(WadSkinLoader::new(bytes)?, "skin"),
+            ),
+        };
+
+        Ok(AddonFile {
+            name,
+            skins,
+            sprays,
+        })
+    }
+
+    /// Reads an unpacked addon folder, laid out like a pk3.
+    pub fn read_dir(name: impl Into<String>, path: &Path) -> Result<AddonFile, Report> {
+        Ok(AddonFile {
+            name: name.into(),
+            sprays: collect(DirSprayLoader::new(path)?, "spray"),
+            skins: collect(DirSkinLoader::new(path)?, "skin"),
+        })
+    }
+}
+
+/// Collects the results of a loader, logging the failures.
+fn collect<T, E>(loader: impl Iterator<Item = Result<T, E>>, what: &str) -> Vec<T>
+where
+    Report: From<E>,
+{
+    loader
+        .filter_map(|item| match item {
+            Ok(item) => Some(item),
+            Err(err) => {
+                error!(
+                    "{:?}",
+                    Report::from(err).wrap_err(format!("failed reading {}", what))
+                );
+                None
+            }
+        })
+        .collect()
+}
+
+impl Default for LoadOrder {
+    fn default() -> Self {
+        LoadOrder {
+            base: sprays(),
+            files: Vec::new(),
+        }
+    }
+}
+
+impl LoadOrder {
+    /// Creates a new load order with only the base game sprays.
+    pub fn new() -> LoadOrder {
+        LoadOrder::default()
+    }
+
+    /// Adds an addon to the end of the load order.
+    ///
+    /// If an addon with the same name is already loaded, it is replaced in
+    /// place instead, keeping its position.
+    pub fn push(&mut self, file: AddonFile) -> &AddonFile {
+        match self.files.iter().position(|other| other.name == file.name) {
+            Some(ix) => {
+                self.files[ix] = file;
+                &self.files[ix]
+            }
+            None => {
+                self.files.push(file);
+                self.files.last().expect("just pushed")
+            }
+        }
+    }
+
+    /// Removes an addon from the load order.
+    pub fn remove(&mut self, name: &str) -> Option<AddonFile> {
+        let ix = self.files.iter().position(|file| file.name == name)?;
+        Some(self.files.remove(ix))
+    }
+
+    /// The loaded addons, in load order.
+    pub fn files(&self) -> &[AddonFile] {
+        &self.files
+    }
+
+    /// Finds a skin by name.
+    ///
+    /// Later addons take priority.
+    pub fn skin(&self, name: &str) -> Option<&Skin> {
+        self.files
+            .iter()
+            .rev()
+            .flat_map(|file| file.skins.iter().rev())
+            .find(|skin| skin.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Finds a spray by id or name.
+    ///
+    /// Later addons take priority, and the base game comes first.
+    pub fn spray(&self, name: &str) -> Option<&Spray> {
+        self.all_sprays().rev().find(|spray| {
+            spray.id.eq_ignore_ascii_case(name) || spray.name.eq_ignore_ascii_case(name)
+        })
+    }
+
+    /// Every skin that isn't overridden, in the order they were first
+    /// loaded.
+    pub fn skins(&self) -> Vec<&Skin> {
+        self.live_skins()
+            .into_iter()
+            .map(|(_, skin)| skin)
+            .collect()
+    }
+
+    /// Every spray that isn't overridden, in the order they were first
+    /// loaded.
+    pub fn sprays(&self) -> Vec<&Spray> {
+        let mut sprays = Vec::<&Spray>::new();
+
+        for spray in self.all_sprays() {
+            match sprays
+                .iter()
+                .position(|other| other.id.eq_ignore_ascii_case(&spray.id))
+            {
+                Some(ix) => sprays[ix] = spray,
+                None => sprays.push(spray),
+            }
+        }
+
+        sprays
+    }
+
+    /// Finds the spray a skin prefers.
+    pub fn prefcolor(&self, skin: &Skin) -> Option<&Spray> {
+        self.spray(&skin.prefcolor)
+    }
+
+    /// Reports what got overridden, and what references stayed unresolved.
+    pub fn report(&self) -> LoadReport {
+        let mut report = LoadReport::default();
+
+        // overrides
+        let mut seen_skins = Vec::<(&str, &str)>::new();
+        let mut seen_sprays = self
+            .base
+            .iter()
+            .map(|spray| (spray.id.as_str(), None))
+            .collect::<Vec<(&str, Option<&str>)>>();
+
+        for file in self.files.iter() {
+            for skin in file.skins.iter() {
+                match seen_skins
+                    .iter_mut()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(&skin.name))
+                {
+                    Some((_, owner)) => {
+                        report.overridden.push(Override {
+                            kind: EntryKind::Skin,
+                            id: skin.name.clone(),
+                            file: Some(owner.to_string()),
+                            by: file.name.clone(),
+                        });
+                        *owner = &file.name;
+                    }
+                    None => seen_skins.push((&skin.name, &file.name)),
+                }
+            }
+
+            for spray in file.sprays.iter() {
+                match seen_sprays
+                    .iter_mut()
+                    .find(|(id, _)| id.eq_ignore_ascii_case(&spray.id))
+                {
+                    Some((_, owner)) => {
+                        report.overridden.push(Override {
+                            kind: EntryKind::Spray,
+                            id: spray.id.clone(),
+                            file: owner.map(ToOwned::to_owned),
+                            by: file.name.clone(),
+                        });
+                        *owner = Some(&file.name);
+                    }
+                    None => seen_sprays.push((&spray.id, Some(&file.name))),
+                }
+            }
+        }
+
+        // unresolved references, only of the skins that are actually used
+        for (file, skin) in self.live_skins() {
+            let mut unresolved = |field: &'static str, value: &str| {
+                report.unresolved.push(Unresolved {
+                    file: file.name.clone(),
+                    skin: skin.name.clone(),
+                    field,
+                    value: value.to_owned(),
+                })
+            };
+
+            if self.spray(&skin.prefcolor).is_none() {
+                unresolved("prefcolor", &skin.prefcolor);
+            }
+            if let Some(prefoffcolor) = &skin.prefoffcolor {
+                if self.spray(prefoffcolor).is_none() {
+                    unresolved("prefoffcolor", prefoffcolor);
+                }
+            }
+            for rival in skin.rivals.iter() {
+                if self.skin(rival).is_none() {
+                    unresolved("rivals", rival);
+                }
+            }
+        }
+
+        report
+    }
+
+    /// Every skin that isn't overridden, with the addon it came from.
+    fn live_skins(&self) -> Vec<(&AddonFile, &Skin)> {
+        let mut skins = Vec::<(&AddonFile, &Skin)>::new();
+
+        for file in self.files.iter() {
+            for skin in file.skins.iter() {
+                match skins
+                    .iter()
+                    .position(|(_, other)| other.name.eq_ignore_ascii_case(&skin.name))
+                {
+                    Some(ix) => skins[ix] = (file, skin),
+                    None => skins.push((file, skin)),
+                }
+            }
+        }
+
+        skins
+    }
+
+    fn all_sprays(&self) -> impl DoubleEndedIterator<Item = &Spray> {
+        self.base
+            .iter()
+            .chain(self.files.iter().flat_map(|file| file.sprays.iter()))
+    }
+}
+
+/// What happened while putting a [`LoadOrder`] together.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LoadReport {
+    /// Skins and sprays replaced by a later addon, in load order.
+    pub overridden: Vec<Override>,
+    /// References to skins or sprays that no addon provides.
+    pub unresolved: Vec<Unresolved>,
+}
+
+/// The kind of thing an addon provides.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum EntryKind {
+    #[display("skin")]
+    Skin,
+    #[display("spray")]
+    Spray,
+}
+
+/// A skin or spray replaced by a later addon.
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+#[display("{kind} \"{id}\" from {} overridden by {by}", file.as_deref().unwrap_or("the base game"))]
+pub struct Override {
+    /// What was replaced.
+    pub kind: EntryKind,
+    /// The skin name or spray id.
+    pub id: String,
+    /// The addon it came from, or `None` for the base game.
+    pub file: Option<String>,
+    /// The addon that replaced it.
+    pub by: String,
+}
+
+/// A reference from a skin that nothing loaded provides.
+#[derive(Clone, Debug, Display, PartialEq, Eq)]
+#[display("skin \"{skin}\" from {file} has unknown {field} \"{value}\"")]
+pub struct Unresolved {
+    /// The addon of the skin.
+    pub file: String,
+    /// The name of the skin.
+    pub skin: String,
+    /// The `S_SKIN` field holding the reference.
+    pub field: &'static str,
+    /// The name that could not be found.
+    pub value: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{Cursor, Write};
+
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    use crate::testing::COLORS_SOC;
+
+    fn pk3(files: &[(&str, &str)]) -> Vec<u8> {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+
+        for (name, text) in files {
+            zip.start_file(*name, SimpleFileOptions::default()).unwrap();
+            zip.write_all(text.as_bytes()).unwrap();
+        }
+
+        zip.finish().unwrap().into_inner()
+    }
+
+    #[test]
+    fn test_load_order() {
+        let colors = pk3(&[("soc/colors.soc", COLORS_SOC)]);
+        let sakura = |realname: &str| {
+            pk3(&[(
+                "skins/sakura/S_SKIN",
+                &format!(
+                    "name = sakura\nrealname = {}\nprefcolor = Maize\nrivals = sonic",
+                    realname
+                ),
+            )])
+        };
+
+        let mut load_order = LoadOrder::new();
+        load_order.push(AddonFile::read("sakura.pk3", sakura("Sakura")).unwrap());
+
+        let report = load_order.report();
+        assert_eq!(
+            report
+                .unresolved
+                .iter()
+                .map(|unresolved| (unresolved.field, unresolved.value.as_str()))
+                .collect::<Vec<_>>(),
+            &[("prefcolor", "Maize"), ("rivals", "sonic")]
+        );
+
+        load_order.push(AddonFile::read("colors.pk3", colors).unwrap());
+        load_order.push(AddonFile::read("sakura2.pk3", sakura("Sakura_2")).unwrap());
+
+        let skin = load_order.skin("SAKURA").unwrap();
+        assert_eq!(skin.realname, "Sakura_2");
+        assert_eq!(load_order.prefcolor(skin).unwrap().id, "SKINCOLOR_MAIZE");
+        assert_eq!(load_order.skins().len(), 1);
+
+        let report = load_order.report();
+        assert_eq!(
+            report.overridden,
+            &[Override {
+                kind: EntryKind::Skin,
+                id: "sakura".into(),
+                file: Some("sakura.pk3".into()),
+                by: "sakura2.pk3".into(),
+            }]
+        );
+        assert_eq!(
+            report.unresolved,
+            &[Unresolved {
+                file: "sakura2.pk3".into(),
+                skin: "sakura".into(),
+                field: "rivals",
+                value: "sonic".into(),
+            }]
+        );
+    }
+}
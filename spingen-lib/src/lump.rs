@@ -3,13 +3,16 @@
 use zip::ZipArchive;
 
 use std::fmt::{self, Debug, Formatter};
-use std::io::{Cursor, Read as _};
+use std::fs;
+use std::io::{self, Cursor, Read as _};
+use std::path::{Path, PathBuf};
 
 use crate::Error;
 
 use bytes::Bytes;
 
-/// A data storage that's either a reference to a pk3 or just raw bytes.
+/// A data storage that's either a reference to a pk3, a file on disk, or just
+/// raw bytes.
 ///
 /// Cheap-to-clone.
 #[derive(Clone, Debug)]
@@ -31,11 +34,22 @@ impl Lump {
             inner: LumpInner::Zip(zip, ix),
         }
     }
+
+    /// Creates a new lump from a file on disk.
+    ///
+    /// The file is read every time the lump is, so changes to it show up
+    /// without reloading.
+    pub fn new_from_file(path: impl Into<PathBuf>) -> Lump {
+        Lump {
+            inner: LumpInner::File(path.into()),
+        }
+    }
 }
 
 #[derive(Clone)]
 enum LumpInner {
     Zip(ZipArchive<Cursor<Bytes>>, usize),
+    File(PathBuf),
     Bytes(Bytes),
 }
 
@@ -56,7 +70,34 @@ impl Lump {
 
                 Ok(buf)
             }
+            LumpInner::File(ref path) => Ok(fs::read(path)?),
             LumpInner::Bytes(ref bytes) => Ok(Vec::from(bytes.clone())),
         }
     }
 }
+
+/// Lists every file under a directory, recursively.
+///
+/// Paths are relative to `root`, and sorted so loading is deterministic.
+pub fn files(root: &Path) -> io::Result<Vec<PathBuf>> {
+    fn walk(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+        for entry in fs::read_dir(root.join(dir))? {
+            let entry = entry?;
+            let path = dir.join(entry.file_name());
+
+            if entry.file_type()?.is_dir() {
+                walk(root, &path, files)?;
+            } else {
+                files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    let mut files = Vec::new();
+    walk(root, Path::new(""), &mut files)?;
+    files.sort();
+
+    Ok(files)
+}
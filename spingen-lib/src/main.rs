@@ -0,0 +1,365 @@
+//! Native command-line renderer for `spingen`.
+
+use std::fs;
+use std::io::Cursor;
+use std::path::{Path, PathBuf};
+
+use clap::{Args, Parser, Subcommand};
+
+use eyre::{eyre, Report, WrapErr};
+
+use log::{info, warn};
+
+use spingen::image::{spray_to_image, AnimationFormat, Encoder, GifOptions};
+use spingen::load_order::{AddonFile, LoadOrder};
+use spingen::skin::{FrameNumber, Skin, SpriteAngle};
+use spingen::spray::Spray;
+
+use wad::Name;
+
+/// Generates animations from PK3s and WADs.
+#[derive(Debug, Parser)]
+#[command(version, about)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Lists all skins and sprays found in the addons.
+    List {
+        /// The addons to load, in order.
+        files: Vec<PathBuf>,
+    },
+    /// Renders a sprite of a skin.
+    Skin(SkinArgs),
+    /// Renders a sprite sheet of a skin, with a JSON descriptor.
+    Sheet(SheetArgs),
+    /// Renders a spraycan.
+    Spray(SprayArgs),
+}
+
+#[derive(Debug, Args)]
+struct SkinArgs {
+    /// The addons to load, in order.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// The name of the skin to render.
+    #[arg(short = 'k', long)]
+    skin: String,
+    /// The 4-character sprite identifier.
+    #[arg(short = 'S', long, default_value = "STIN")]
+    sprite: String,
+    /// The frame of the sprite.
+    #[arg(short, long, default_value = "A")]
+    frame: char,
+    /// Animates every frame of the sprite at this angle, instead of spinning
+    /// through the angles of one frame.
+    #[arg(short, long, conflicts_with = "frame")]
+    angle: Option<char>,
+    /// The spray to apply, by id or name.
+    ///
+    /// Defaults to the skin's preferred color.
+    #[arg(short, long)]
+    spray: Option<String>,
+    /// The factor to upscale by.
+    #[arg(long, default_value_t = 1.)]
+    scale: f32,
+    /// The delay between each frame, in centiseconds.
+    #[arg(long, default_value_t = 20)]
+    delay: u16,
+    /// Mirrors the sprite across the X axis.
+    #[arg(long)]
+    mirror: bool,
+    /// The format to encode animations in, `gif`, `apng` or `webp`.
+    #[arg(long, default_value_t = AnimationFormat::Gif)]
+    format: AnimationFormat,
+    /// Draws the sprites at their native resolution, ignoring the skin's
+    /// `highresscale`.
+    #[arg(long)]
+    native: bool,
+    /// Where to write the image.
+    ///
+    /// Defaults to `<skin>_<sprite><frame>.<ext>` in the working directory.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct SheetArgs {
+    /// The addons to load, in order.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// The name of the skin to render.
+    #[arg(short = 'k', long)]
+    skin: String,
+    /// The 4-character sprite identifier.
+    ///
+    /// Defaults to every sprite of the skin.
+    #[arg(short = 'S', long)]
+    sprite: Option<String>,
+    /// The spray to apply, by id or name.
+    ///
+    /// Defaults to the skin's preferred color.
+    #[arg(short, long)]
+    spray: Option<String>,
+    /// The factor to upscale by.
+    #[arg(long, default_value_t = 1.)]
+    scale: f32,
+    /// Draws the sprites at their native resolution, ignoring the skin's
+    /// `highresscale`.
+    #[arg(long)]
+    native: bool,
+    /// Where to write the sheet.
+    ///
+    /// Defaults to `<skin>[_<sprite>].png` in the working directory. The
+    /// descriptor is written next to it, with a `.json` extension.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct SprayArgs {
+    /// The addons to load, in order.
+    #[arg(required = true)]
+    files: Vec<PathBuf>,
+    /// The spray to render, by id or name.
+    #[arg(short, long)]
+    spray: String,
+    /// Where to write the image.
+    ///
+    /// Defaults to `<spray>.png` in the working directory.
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+/// Everything loaded from the addons.
+#[derive(Debug)]
+struct Addons(LoadOrder);
+
+impl Addons {
+    /// Loads the base game sprays, then every addon in order.
+    fn load(files: &[PathBuf]) -> Result<Addons, Report> {
+        let mut load_order = LoadOrder::new();
+
+        for path in files {
+            let file = Addons::load_file(path)
+                .wrap_err_with(|| format!("failed to load \"{}\"", path.display()))?;
+            load_order.push(file);
+        }
+
+        let report = load_order.report();
+        for overridden in report.overridden.iter() {
+            info!("{}", overridden);
+        }
+        for unresolved in report.unresolved.iter() {
+            warn!("{}", unresolved);
+        }
+
+        Ok(Addons(load_order))
+    }
+
+    fn load_file(path: &Path) -> Result<AddonFile, Report> {
+        let name = path.display().to_string();
+
+        if path.is_dir() {
+            // an unpacked addon, laid out like a pk3
+            AddonFile::read_dir(name, path)
+        } else {
+            AddonFile::read(name, fs::read(path)?)
+        }
+    }
+
+    /// Finds a skin by name.
+    ///
+    /// Later addons take priority.
+    fn skin(&self, name: &str) -> Result<&Skin, Report> {
+        self.0
+            .skin(name)
+            .ok_or_else(|| eyre!("skin \"{}\" not found", name))
+    }
+
+    /// Finds a spray to apply to a skin.
+    ///
+    /// Falls back to the skin's preferred color.
+    fn skin_spray(&self, skin: &Skin, name: Option<&str>) -> Result<&Spray, Report> {
+        match name {
+            Some(name) => self
+                .spray(name)
+                .ok_or_else(|| eyre!("spray \"{}\" not found", name)),
+            None => Ok(self.0.prefcolor(skin).unwrap_or_else(|| {
+                warn!("invalid prefcolor {:?}, using default", skin.prefcolor);
+                self.0.sprays()[0]
+            })),
+        }
+    }
+
+    /// Finds a spray by id or name.
+    ///
+    /// Later addons take priority.
+    fn spray(&self, name: &str) -> Option<&Spray> {
+        self.0.spray(name)
+    }
+}
+
+fn main() -> Result<(), Report> {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    match Cli::parse().command {
+        Command::List { files } => list(files),
+        Command::Skin(args) => render_skin(args),
+        Command::Sheet(args) => render_sheet(args),
+        Command::Spray(args) => render_spray(args),
+    }
+}
+
+fn list(files: Vec<PathBuf>) -> Result<(), Report> {
+    let addons = Addons::load(&files)?;
+
+    for skin in addons.0.skins() {
+        let sprites = skin
+            .sprites()
+            .into_iter()
+            .map(|name| name.as_str().to_owned())
+            .collect::<Vec<_>>();
+
+        println!(
+            "skin {} \"{}\" (speed {}, weight {}, prefcolor {})",
+            skin.name,
+            skin.realname.replace('_', " "),
+            skin.kartspeed,
+            skin.kartweight,
+            skin.prefcolor,
+        );
+        println!("  sprites: {}", sprites.join(" "));
+    }
+
+    for spray in addons.0.sprays() {
+        println!("spray {} \"{}\"", spray.id, spray.name);
+    }
+
+    Ok(())
+}
+
+fn render_skin(args: SkinArgs) -> Result<(), Report> {
+    let addons = Addons::load(&args.files)?;
+
+    let name = Name::from_bytes(args.sprite.to_ascii_uppercase().as_bytes())
+        .wrap_err("invalid `sprite` parameter")?;
+    let frame = u8::try_from(args.frame)
+        .ok()
+        .and_then(FrameNumber::from_ascii_char)
+        .ok_or_else(|| eyre!("invalid `frame` parameter: {:?}", args.frame))?;
+
+    let skin = addons.skin(&args.skin)?;
+    let spray = addons.skin_spray(skin, args.spray.as_deref())?;
+
+    let options = GifOptions {
+        scale: args.scale,
+        delay: args.delay,
+        mirror: args.mirror,
+        format: args.format,
+        native: args.native,
+    };
+
+    let mut encoder = Encoder::new(skin).with_spray(spray);
+
+    if let Some(resolved) = skin
+        .resolve(&name)
+        .filter(|resolved| resolved.is_fallback())
+    {
+        warn!(
+            "skin has no {} sprite, falling back to {}",
+            resolved.requested, resolved.name
+        );
+    }
+
+    let mut buf = Vec::new();
+    let (suffix, encoded_kind) = match args.angle {
+        Some(angle) => {
+            let angle = u8::try_from(angle)
+                .ok()
+                .and_then(SpriteAngle::from_ascii_char)
+                .ok_or_else(|| eyre!("invalid `angle` parameter: {:?}", angle))?;
+            let encoded_kind = encoder
+                .sprite_frames_with_options(Cursor::new(&mut buf), name, angle, options)
+                .wrap_err("failed to encode gif")?;
+            (format!("{}", angle.into_inner() as char), encoded_kind)
+        }
+        None => {
+            let encoded_kind = encoder
+                .sprite_gif_with_options(Cursor::new(&mut buf), name, frame, options)
+                .wrap_err("failed to encode gif")?;
+            (frame.to_string(), encoded_kind)
+        }
+    };
+
+    let output = args.output.unwrap_or_else(|| {
+        PathBuf::from(format!(
+            "{}_{}{}.{}",
+            skin.name,
+            name,
+            suffix,
+            encoded_kind.as_extension()
+        ))
+    });
+
+    fs::write(&output, buf).wrap_err_with(|| format!("failed to write \"{}\"", output.display()))
+}
+
+fn render_sheet(args: SheetArgs) -> Result<(), Report> {
+    let addons = Addons::load(&args.files)?;
+
+    let skin = addons.skin(&args.skin)?;
+    let spray = addons.skin_spray(skin, args.spray.as_deref())?;
+
+    let options = GifOptions {
+        scale: args.scale,
+        native: args.native,
+        ..Default::default()
+    };
+
+    let mut encoder = Encoder::new(skin).with_spray(spray);
+
+    let mut buf = Vec::new();
+    let (output, sheet) = match args.sprite {
+        Some(ref sprite) => {
+            let name = Name::from_bytes(sprite.to_ascii_uppercase().as_bytes())
+                .wrap_err("invalid `sprite` parameter")?;
+            let sheet = encoder.sprite_sheet(Cursor::new(&mut buf), name, options);
+            (format!("{}_{}.png", skin.name, name), sheet)
+        }
+        None => {
+            let sheet = encoder.skin_sheet(Cursor::new(&mut buf), options);
+            (format!("{}.png", skin.name), sheet)
+        }
+    };
+    let sheet = sheet.wrap_err("failed to encode sprite sheet")?;
+
+    let output = args.output.unwrap_or_else(|| PathBuf::from(output));
+    let json = serde_json::to_string_pretty(&sheet)?;
+
+    fs::write(&output, buf)
+        .wrap_err_with(|| format!("failed to write \"{}\"", output.display()))?;
+
+    let output = output.with_extension("json");
+    fs::write(&output, json).wrap_err_with(|| format!("failed to write \"{}\"", output.display()))
+}
+
+fn render_spray(args: SprayArgs) -> Result<(), Report> {
+    let addons = Addons::load(&args.files)?;
+
+    let spray = addons
+        .spray(&args.spray)
+        .ok_or_else(|| eyre!("spray \"{}\" not found", args.spray))?;
+
+    let mut buf = Vec::new();
+    spray_to_image(Cursor::new(&mut buf), spray).wrap_err("failed to generate spraycan graphic")?;
+
+    let output = args
+        .output
+        .unwrap_or_else(|| PathBuf::from(format!("{}.png", spray.name)));
+
+    fs::write(&output, buf).wrap_err_with(|| format!("failed to write \"{}\"", output.display()))
+}
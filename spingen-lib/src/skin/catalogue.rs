@@ -0,0 +1,249 @@
+//! The Ring Racers player sprite catalogue.
+//!
+//! Skins only name their sprites by 4-character SPR2 identifiers, like `STIN`
+//! or `DRLN`. This is a built-in table of the ones the game uses, so they can
+//! be shown with a proper name.
+
+use derive_more::Display;
+
+use wad::Name;
+
+/// A group of related player sprites.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Spr2Category {
+    /// Standing still, revving the engine.
+    #[display("still")]
+    Still,
+    /// Driving slowly.
+    #[display("slow driving")]
+    SlowDriving,
+    /// Driving fast.
+    #[display("fast driving")]
+    FastDriving,
+    /// Drifting left or right.
+    #[display("drifting")]
+    Drifting,
+    /// Getting hit, or worse.
+    #[display("hurt")]
+    Hurt,
+    /// The signpost at the finish line.
+    #[display("signpost")]
+    Signpost,
+    /// The Wanted poster.
+    #[display("wanted")]
+    Wanted,
+    /// The dialogue portrait.
+    #[display("dialogue")]
+    Dialogue,
+    /// Any sprite not in the catalogue.
+    #[display("other")]
+    Other,
+}
+
+/// Information about a player sprite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Spr2Info {
+    /// The 4-character SPR2 identifier.
+    pub id: &'static str,
+    /// The human-readable name.
+    pub name: &'static str,
+    /// The group the sprite belongs to.
+    pub category: Spr2Category,
+    /// Whether the game cycles through the frames of the sprite.
+    ///
+    /// The rest use their frames for different things, like the faces of the
+    /// Wanted poster.
+    pub animates: bool,
+}
+
+impl Spr2Info {
+    /// Creates a new `Spr2Info`.
+    pub const fn new(
+        id: &'static str,
+        name: &'static str,
+        category: Spr2Category,
+        animates: bool,
+    ) -> Spr2Info {
+        Spr2Info {
+            id,
+            name,
+            category,
+            animates,
+        }
+    }
+
+    /// Looks up a sprite in the catalogue.
+    pub fn get(name: &Name) -> Option<&'static Spr2Info> {
+        CATALOGUE
+            .iter()
+            .find(|info| info.id.as_bytes().eq_ignore_ascii_case(name))
+    }
+
+    /// The position of a sprite in the catalogue.
+    ///
+    /// Sorting by this puts sprites in the order of the catalogue, with
+    /// unknown sprites last.
+    pub fn position(name: &Name) -> usize {
+        CATALOGUE
+            .iter()
+            .position(|info| info.id.as_bytes().eq_ignore_ascii_case(name))
+            .unwrap_or(CATALOGUE.len())
+    }
+}
+
+/// Every player sprite of Ring Racers, in the order the game defines them.
+pub static CATALOGUE: &[Spr2Info] = &[
+    Spr2Info::new("STIN", "Still", Spr2Category::Still, true),
+    Spr2Info::new("STIL", "Still Left", Spr2Category::Still, true),
+    Spr2Info::new("STIR", "Still Right", Spr2Category::Still, true),
+    Spr2Info::new(
+        "STGL",
+        "Still Left (glance back)",
+        Spr2Category::Still,
+        true,
+    ),
+    Spr2Info::new(
+        "STGR",
+        "Still Right (glance back)",
+        Spr2Category::Still,
+        true,
+    ),
+    Spr2Info::new("STLL", "Still Left (look back)", Spr2Category::Still, true),
+    Spr2Info::new("STLR", "Still Right (look back)", Spr2Category::Still, true),
+    Spr2Info::new("SLWN", "Slow Driving", Spr2Category::SlowDriving, true),
+    Spr2Info::new("SLWL", "Slow Driving Left", Spr2Category::SlowDriving, true),
+    Spr2Info::new(
+        "SLWR",
+        "Slow Driving Right",
+        Spr2Category::SlowDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "SLGL",
+        "Slow Driving Left (glance back)",
+        Spr2Category::SlowDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "SLGR",
+        "Slow Driving Right (glance back)",
+        Spr2Category::SlowDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "SLLL",
+        "Slow Driving Left (look back)",
+        Spr2Category::SlowDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "SLLR",
+        "Slow Driving Right (look back)",
+        Spr2Category::SlowDriving,
+        true,
+    ),
+    Spr2Info::new("FSTN", "Fast Driving", Spr2Category::FastDriving, true),
+    Spr2Info::new("FSTL", "Fast Driving Left", Spr2Category::FastDriving, true),
+    Spr2Info::new(
+        "FSTR",
+        "Fast Driving Right",
+        Spr2Category::FastDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "FSGL",
+        "Fast Driving Left (glance back)",
+        Spr2Category::FastDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "FSGR",
+        "Fast Driving Right (glance back)",
+        Spr2Category::FastDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "FSLL",
+        "Fast Driving Left (look back)",
+        Spr2Category::FastDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "FSLR",
+        "Fast Driving Right (look back)",
+        Spr2Category::FastDriving,
+        true,
+    ),
+    Spr2Info::new(
+        "DRLN",
+        "Drifting Left, Steering Neutral",
+        Spr2Category::Drifting,
+        true,
+    ),
+    Spr2Info::new(
+        "DRLO",
+        "Drifting Left, Steering Outwards",
+        Spr2Category::Drifting,
+        true,
+    ),
+    Spr2Info::new(
+        "DRLI",
+        "Drifting Left, Steering Inwards",
+        Spr2Category::Drifting,
+        true,
+    ),
+    Spr2Info::new(
+        "DRRN",
+        "Drifting Right, Steering Neutral",
+        Spr2Category::Drifting,
+        true,
+    ),
+    Spr2Info::new(
+        "DRRO",
+        "Drifting Right, Steering Outwards",
+        Spr2Category::Drifting,
+        true,
+    ),
+    Spr2Info::new(
+        "DRRI",
+        "Drifting Right, Steering Inwards",
+        Spr2Category::Drifting,
+        true,
+    ),
+    Spr2Info::new("SPIN", "Spinout", Spr2Category::Hurt, false),
+    Spr2Info::new("DEAD", "Dead", Spr2Category::Hurt, false),
+    Spr2Info::new("SIGN", "Finish Signpost", Spr2Category::Signpost, false),
+    Spr2Info::new(
+        "SIGL",
+        "Finish Signpost, Ironman Perfect",
+        Spr2Category::Signpost,
+        false,
+    ),
+    Spr2Info::new(
+        "SSIG",
+        "\"working designs\" Signpost",
+        Spr2Category::Signpost,
+        false,
+    ),
+    Spr2Info::new("XTRA", "Wanted", Spr2Category::Wanted, false),
+    Spr2Info::new("TALK", "Dialogue Icon", Spr2Category::Dialogue, false),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_catalogue() {
+        let stin = "STIN".parse::<Name>().unwrap();
+        let xtra = "XTRA".parse::<Name>().unwrap();
+        let abcd = "ABCD".parse::<Name>().unwrap();
+
+        assert_eq!(Spr2Info::get(&stin).unwrap().name, "Still");
+        assert!(!Spr2Info::get(&xtra).unwrap().animates);
+        assert_eq!(Spr2Info::get(&abcd), None);
+
+        assert!(Spr2Info::position(&stin) < Spr2Info::position(&xtra));
+        assert_eq!(Spr2Info::position(&abcd), CATALOGUE.len());
+    }
+}
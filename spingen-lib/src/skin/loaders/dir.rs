@@ -0,0 +1,161 @@
+//! Unpacked folder loaders.
+//!
+//! Addons are usually developed as a folder laid out like a pk3, and only
+//! zipped up for release. These loaders read that folder directly.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ahash::HashMap;
+
+use wad::Name;
+
+use crate::doom::skin::SkinDefine;
+use crate::lump::{self, Lump};
+use crate::skin::{Error, Skin};
+
+use super::{folder_skin, lookup_name, lump_name};
+
+/// A folder skin loader.
+#[derive(Clone, Debug)]
+pub struct DirSkinLoader {
+    root: PathBuf,
+    files: Vec<PathBuf>,
+    /// Every file by lump name, for finding portraits.
+    lumps: HashMap<Name, usize>,
+    file_index: usize,
+}
+
+impl DirSkinLoader {
+    /// Creates a new folder loader.
+    pub fn new(root: impl Into<PathBuf>) -> Result<DirSkinLoader, Error> {
+        let root = root.into();
+        let files = lump::files(&root)?;
+
+        // like the game, the last file with a name wins
+        let lumps = files
+            .iter()
+            .enumerate()
+            .filter_map(|(i, path)| {
+                let name = path
+                    .file_name()
+                    .and_then(|name| lump_name(Path::new(name)))?;
+                Some((lookup_name(&name), i))
+            })
+            .collect();
+
+        Ok(DirSkinLoader {
+            root,
+            files,
+            lumps,
+            file_index: 0,
+        })
+    }
+
+    fn read_skin(&self, ix: usize) -> Result<Skin, Error> {
+        let path = &self.files[ix];
+
+        // get path prefix
+        let skin_path = path.parent().unwrap_or(Path::new(""));
+
+        // parse s_skin
+        let s_skin = fs::read_to_string(self.root.join(path))?;
+        let skin_define = SkinDefine::read(&s_skin)
+            .map_err(|err| Error::Skin(skin_path.display().to_string(), err))?;
+
+        // read all related sprites
+        let lumps = self.files.iter().filter_map(|path| {
+            let name = path.strip_prefix(skin_path).ok().and_then(lump_name)?;
+            Some((name, Lump::new_from_file(self.root.join(path))))
+        });
+
+        let find_lump = |name: &Name| {
+            let i = *self.lumps.get(&lookup_name(name))?;
+            Some(Lump::new_from_file(self.root.join(&self.files[i])))
+        };
+
+        Ok(folder_skin(skin_define, lumps, find_lump))
+    }
+}
+
+impl Iterator for DirSkinLoader {
+    type Item = Result<Skin, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.file_index < self.files.len() {
+            let file_index = self.file_index;
+            self.file_index += 1;
+
+            // find next S_SKIN define
+            let Some(name) = self.files[file_index].file_stem().and_then(|s| s.to_str()) else {
+                continue;
+            };
+
+            if name.eq_ignore_ascii_case("S_SKIN") {
+                return Some(self.read_skin(file_index));
+            }
+        }
+
+        None
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.files.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::skin::{portrait::PortraitKind, FrameNumber};
+    use crate::testing::write;
+
+    const PATCH: &[u8] = include_bytes!("../../SPCNK0.lmp");
+
+    #[test]
+    fn test_dir_skin() {
+        let dir = tempfile::tempdir().unwrap();
+
+        write(
+            dir.path(),
+            "skins/sakura/S_SKIN",
+            b"name = sakura\nrealname = Sakura_Haruno\nprefcolor = Pink\n",
+        );
+        write(dir.path(), "skins/sakura/STINA1.lmp", PATCH);
+        write(dir.path(), "skins/sakura/STINA2A8.lmp", PATCH);
+        write(dir.path(), "skins/sakura/STINz1.lmp", PATCH);
+        write(
+            dir.path(),
+            "skins/tails/S_SKIN",
+            b"name = tails\nrealname = Tails\nprefcolor = Orange\nfacerank = TAILRANK\n",
+        );
+        write(dir.path(), "skins/tails/SPINA1.lmp", PATCH);
+        write(dir.path(), "graphics/faces/TAILRANK.lmp", PATCH);
+
+        let skins = DirSkinLoader::new(dir.path())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(skins.len(), 2);
+
+        let stin = "STIN".parse::<Name>().unwrap();
+        let spin = "SPIN".parse::<Name>().unwrap();
+
+        let sakura = &skins[0];
+        assert_eq!(sakura.name, "sakura");
+        assert_eq!(sakura.iter().collect::<Vec<_>>(), &[stin]);
+        assert_eq!(sakura.iter_angles(&stin, FrameNumber::A).count(), 3);
+        let z = FrameNumber::from_ascii_char(b'z').unwrap();
+        assert_eq!(sakura.iter_angles(&stin, z).count(), 1);
+
+        let tails = &skins[1];
+        assert_eq!(tails.name, "tails");
+        assert_eq!(tails.iter().collect::<Vec<_>>(), &[spin]);
+
+        let patch = tails.read(&"SPINA1".parse::<Name>().unwrap()).unwrap();
+        assert!(patch.width > 0 && patch.height > 0);
+        assert!(tails.portrait(PortraitKind::Rank).is_ok());
+        assert!(!sakura.has_portrait(PortraitKind::Rank));
+    }
+}
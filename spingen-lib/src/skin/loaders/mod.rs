@@ -1,7 +1,104 @@
 //! Skin and spray loaders.
 
+pub mod dir;
 pub mod pk3;
 pub mod wad;
 
+pub use dir::DirSkinLoader;
 pub use pk3::Pk3SkinLoader;
 pub use wad::WadSkinLoader;
+
+use std::path::Path;
+use std::sync::Arc;
+
+use ::wad::Name;
+
+use crate::doom::skin::SkinDefine;
+use crate::lump::Lump;
+
+use super::{
+    portrait::{PortraitKind, Portraits},
+    spr2, Skin,
+};
+
+/// Gets the lump name of a file in a skin folder, without its extension.
+fn lump_name(path: &Path) -> Option<Name> {
+    let name = path.to_str()?;
+    let name = match name.rfind('.') {
+        // strip ext
+        Some(ix) => &name[..ix],
+        None => name,
+    };
+
+    // the folder containing the skin has no name, and the case is kept, since
+    // frames a-z are different from frames A-Z
+    Name::from_bytes(name.as_bytes())
+        .ok()
+        .filter(|name| !name.as_str().is_empty())
+}
+
+/// Gets the name to find a lump by, which ignores case.
+fn lookup_name(name: &Name) -> Name {
+    Name::from_bytes(&name.to_ascii_uppercase()).expect("valid name")
+}
+
+/// Builds a skin out of the lumps in its folder, in order.
+///
+/// Like the game, portraits are looked up by name anywhere in the addon with
+/// `find_lump`, though lumps in the skin's own folder come first.
+fn folder_skin(
+    skin_define: SkinDefine,
+    lumps: impl IntoIterator<Item = (Name, Lump)>,
+    find_lump: impl Fn(&Name) -> Option<Lump>,
+) -> Skin {
+    let mut index = spr2::Index::default();
+    let mut portraits = Portraits::default();
+    let mut in_sounds = false;
+
+    for (name, lump) in lumps {
+        match lookup_name(&name).as_str() {
+            "DS_START" => {
+                in_sounds = true;
+                continue;
+            }
+            "DS_END" => {
+                in_sounds = false;
+                continue;
+            }
+            // skip skin lump
+            "S_SKIN" => continue,
+            // skip if within sounds bounds
+            _ if in_sounds => continue,
+            _ => (),
+        }
+
+        // portraits are looked up by name, not as sprites
+        if Portraits::is_portrait(&skin_define, &name) {
+            portraits.add(name, lump);
+            continue;
+        }
+
+        // read patch data
+        if let Err(err) = index.add(name, lump) {
+            warn!("{:?}", err);
+        }
+    }
+
+    for kind in PortraitKind::ALL {
+        let Ok(name) = kind.lump_name(&skin_define).parse::<Name>() else {
+            continue;
+        };
+
+        if !portraits.contains(name.as_str()) {
+            if let Some(lump) = find_lump(&name) {
+                portraits.add(name, lump);
+            }
+        }
+    }
+
+    Skin {
+        skin: Arc::new(skin_define),
+        index: Arc::new(index),
+        portraits: Arc::new(portraits),
+    }
+}
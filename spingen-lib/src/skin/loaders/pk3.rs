@@ -2,21 +2,27 @@
 
 use std::io::{Cursor, Read};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
 
 use crate::doom::skin::SkinDefine;
 use crate::lump::Lump;
-use crate::skin::{spr2, Error, Skin};
+use crate::skin::{Error, Skin};
+
+use super::{folder_skin, lookup_name, lump_name};
+
+use ahash::HashMap;
 
 use bytes::Bytes;
 
 use wad::Name;
+
 use zip::ZipArchive;
 
 /// A PK3 skin loader.
 #[derive(Clone, Debug)]
 pub struct Pk3SkinLoader {
     zip: ZipArchive<Cursor<Bytes>>,
+    /// Every file by lump name, for finding portraits.
+    lumps: HashMap<Name, usize>,
     file_index: usize,
 }
 
@@ -24,9 +30,25 @@ impl Pk3SkinLoader {
     /// Creates a new PK3 loader.
     pub fn new(bytes: impl Into<Bytes>) -> Result<Pk3SkinLoader, Error> {
         let bytes = bytes.into();
-        ZipArchive::new(Cursor::new(bytes))
-            .map(|zip| Pk3SkinLoader { zip, file_index: 0 })
-            .map_err(From::from)
+        let mut zip = ZipArchive::new(Cursor::new(bytes))?;
+
+        // like the game, the last file with a name wins
+        let mut lumps = HashMap::default();
+        for i in 0..zip.len() {
+            let entry = zip.by_index_raw(i)?;
+            if let Some(name) = Path::new(entry.name())
+                .file_name()
+                .and_then(|name| lump_name(Path::new(name)))
+            {
+                lumps.insert(lookup_name(&name), i);
+            }
+        }
+
+        Ok(Pk3SkinLoader {
+            zip,
+            lumps,
+            file_index: 0,
+        })
     }
 
     fn read_skin(&mut self, ix: usize) -> Result<Skin, Error> {
@@ -49,63 +71,28 @@ impl Pk3SkinLoader {
             .map_err(|err| Error::Skin(skin_path.display().to_string(), err))?;
 
         // read all related sprites
-        let mut index = spr2::Index::default();
-        let mut in_sounds = false;
+        let mut lumps = Vec::new();
 
         for i in 0..self.zip.len() {
             let entry = self.zip.by_index_raw(i)?;
-            let path = Path::new(entry.name());
-
-            if let Ok(name) = path.strip_prefix(&skin_path) {
-                let Some(name) = name
-                    .to_str()
-                    .map(|name| {
-                        if let Some(ix) = name.rfind('.') {
-                            // strip ext
-                            &name[..ix]
-                        } else {
-                            name
-                        }
-                    })
-                    .and_then(|s| s.parse::<Name>().ok())
-                else {
-                    continue;
-                };
-
-                if name.as_str().len() == 0 {
-                    // skip the folder containing the skin
-                    continue;
-                }
-
-                match name.as_str() {
-                    "DS_START" => {
-                        in_sounds = true;
-                        continue;
-                    }
-                    "DS_END" => {
-                        in_sounds = false;
-                        continue;
-                    }
-                    // skip skin lump
-                    "S_SKIN" => continue,
-                    // skip if within sounds bounds
-                    _ if in_sounds => continue,
-                    _ => (),
-                }
 
-                drop(entry);
+            let name = Path::new(entry.name())
+                .strip_prefix(&skin_path)
+                .ok()
+                .and_then(lump_name);
+            drop(entry);
 
-                // read patch data
-                if let Err(err) = index.add(name, Lump::new_from_zip(self.zip.clone(), i)) {
-                    warn!("{:?}", err);
-                }
+            if let Some(name) = name {
+                lumps.push((name, Lump::new_from_zip(self.zip.clone(), i)));
             }
         }
 
-        Ok(Skin {
-            skin: Arc::new(skin_define),
-            index: Arc::new(index),
-        })
+        let find_lump = |name: &Name| {
+            let i = *self.lumps.get(&lookup_name(name))?;
+            Some(Lump::new_from_zip(self.zip.clone(), i))
+        };
+
+        Ok(folder_skin(skin_define, lumps, find_lump))
     }
 }
 
@@ -140,3 +127,94 @@ impl Iterator for Pk3SkinLoader {
         (0, Some(self.zip.len()))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::skin::{portrait::PortraitKind, FrameNumber};
+
+    use std::io::Write;
+
+    use wad::Name;
+    use zip::{write::SimpleFileOptions, ZipWriter};
+
+    const PATCH: &[u8] = include_bytes!("../../SPCNK0.lmp");
+
+    #[test]
+    fn test_pk3_skin() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+
+        zip.start_file("skins/sakura/S_SKIN", options).unwrap();
+        zip.write_all(
+            b"name = sakura\nrealname = Sakura_Haruno\nprefcolor = Pink\nfacerank = SAKRANK\nfacewant = SAKWANT\n",
+        )
+        .unwrap();
+        zip.start_file("skins/sakura/STINA1.lmp", options).unwrap();
+        zip.write_all(PATCH).unwrap();
+        zip.start_file("skins/sakura/STINA2A8.lmp", options)
+            .unwrap();
+        zip.write_all(PATCH).unwrap();
+        zip.start_file("skins/sakura/SAKRANK.lmp", options).unwrap();
+        zip.write_all(PATCH).unwrap();
+        // portraits are found anywhere in the addon
+        zip.start_file("graphics/SAKWANT.lmp", options).unwrap();
+        zip.write_all(PATCH).unwrap();
+
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let skins = Pk3SkinLoader::new(bytes)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(skins.len(), 1);
+
+        let skin = &skins[0];
+        assert_eq!(skin.name, "sakura");
+        assert_eq!(skin.realname, "Sakura_Haruno");
+        assert_eq!(skin.prefcolor, "Pink");
+
+        let name = "STIN".parse::<Name>().unwrap();
+        assert_eq!(skin.iter().collect::<Vec<_>>(), &[name]);
+        assert_eq!(skin.iter_angles(&name, FrameNumber::A).count(), 3);
+
+        let patch = skin.read(&"STINA1".parse::<Name>().unwrap()).unwrap();
+        assert!(patch.width > 0 && patch.height > 0);
+
+        assert!(skin.has_portrait(PortraitKind::Rank));
+        assert!(skin.has_portrait(PortraitKind::Wanted));
+        assert!(!skin.has_portrait(PortraitKind::Minimap));
+        assert!(skin.portrait(PortraitKind::Wanted).is_ok());
+    }
+
+    #[test]
+    fn test_pk3_lowercase_frames() {
+        let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+        let options = SimpleFileOptions::default();
+
+        zip.start_file("skins/sakura/s_skin", options).unwrap();
+        zip.write_all(b"name = sakura\nrealname = Sakura_Haruno\nprefcolor = Pink\n")
+            .unwrap();
+        zip.start_file("skins/sakura/STINA1.lmp", options).unwrap();
+        zip.write_all(PATCH).unwrap();
+        zip.start_file("skins/sakura/STINa1.lmp", options).unwrap();
+        zip.write_all(PATCH).unwrap();
+
+        let bytes = zip.finish().unwrap().into_inner();
+
+        let skins = Pk3SkinLoader::new(bytes)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(skins.len(), 1);
+
+        let name = "STIN".parse::<Name>().unwrap();
+        let mut frames = skins[0]
+            .iter_frames(&name)
+            .map(|frame| frame.to_ascii_char() as char)
+            .collect::<Vec<_>>();
+        frames.sort();
+        assert_eq!(frames, &['A', 'a']);
+    }
+}
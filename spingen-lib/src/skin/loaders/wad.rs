@@ -1,109 +1,107 @@
 //! WAD loaders.
 
 use std::io::{Cursor, Read};
-use std::sync::Arc;
+use std::ops::Range;
 
 use crate::doom::skin::SkinDefine;
 use crate::lump::Lump;
-use crate::skin::{spr2, Skin};
+use crate::skin::{portrait::PortraitKind, Skin};
 use crate::Error;
 
+use super::folder_skin;
+
+use ahash::HashMap;
+
 use bytes::Bytes;
 
-use wad::Archive;
+use wad::{Archive, Name};
 
 /// A WAD skin loader.
+///
+/// A WAD can hold any number of skins. Like in the game, each `S_SKIN` lump
+/// owns the lumps after it, up to the next `S_SKIN`, `S_START` or `S_END`.
 #[derive(Clone, Debug)]
 pub struct WadSkinLoader {
     wad: Archive<Cursor<Bytes>>,
-    consumed: bool,
+    skins: Vec<(usize, Range<usize>)>,
+    /// Every lump by name, for finding portraits.
+    lumps: HashMap<Name, usize>,
+    next: usize,
 }
 
 impl WadSkinLoader {
     /// Creates a new WAD loader.
     pub fn new(bytes: impl Into<Bytes>) -> Result<WadSkinLoader, Error> {
         let bytes = bytes.into();
-        Archive::new(Cursor::new(bytes))
-            .map(|wad| WadSkinLoader {
-                wad,
-                consumed: false,
-            })
-            .map_err(From::from)
+        let mut wad = Archive::new(Cursor::new(bytes))?;
+
+        let names = (0..wad.len())
+            .map(|i| *wad.get(i).expect("valid entry").name())
+            .collect::<Vec<_>>();
+
+        // like the game, the last lump with a name wins
+        let lumps = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| (*name, i))
+            .collect();
+
+        Ok(WadSkinLoader {
+            wad,
+            skins: skin_ranges(&names),
+            lumps,
+            next: 0,
+        })
     }
-}
-
-impl Iterator for WadSkinLoader {
-    type Item = Result<Skin, Error>;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.consumed {
-            return None;
-        }
-
-        self.consumed = true;
-
-        // find S_SKIN lump
-        let mut s_skin = None::<String>;
-        for i in 0..self.wad.len() {
-            let mut entry = self.wad.get(i).expect("valid entry");
 
-            if entry.name().as_str().eq_ignore_ascii_case("S_SKIN") {
-                let mut text = String::with_capacity(entry.bytes_len());
-                match entry.read_to_string(&mut text) {
-                    Ok(_) => (),
-                    Err(err) => return Some(Err(err.into())),
-                }
-                s_skin = Some(text);
+    fn read_skin(&mut self, s_skin: usize, lumps: Range<usize>) -> Result<Skin, Error> {
+        let mut entry = self.wad.get(s_skin).expect("valid entry");
+        let mut text = String::with_capacity(entry.bytes_len());
+        entry.read_to_string(&mut text)?;
+
+        let skin_define = SkinDefine::read(&text)
+            .map_err(|err| Error::Skin(format!("S_SKIN@{}", s_skin), err))?;
+
+        // portraits are found by name anywhere in the wad, like the game does
+        let mut portraits = HashMap::<Name, Lump>::default();
+        for kind in PortraitKind::ALL {
+            let Ok(name) = kind.lump_name(&skin_define).parse::<Name>() else {
+                continue;
+            };
+            if let Some(&i) = self.lumps.get(&name) {
+                portraits.insert(name, self.read_lump(i)?);
             }
         }
 
-        let Some(s_skin) = s_skin else {
-            return None;
-        };
+        // read all related sprites
+        let lumps = lumps
+            .map(|i| {
+                let name = *self.wad.get(i).expect("valid entry").name();
+                Ok((name, self.read_lump(i)?))
+            })
+            .collect::<Result<Vec<_>, Error>>()?;
 
-        let skin_define = match SkinDefine::read(&s_skin) {
-            Ok(skin) => skin,
-            Err(err) => return Some(Err(Error::Skin("S_SKIN".into(), err))),
-        };
+        Ok(folder_skin(skin_define, lumps, |name| {
+            portraits.get(name).cloned()
+        }))
+    }
 
-        // read all related sprites
-        let mut index = spr2::Index::default();
-        let mut in_sounds = false;
-
-        for i in 0..self.wad.len() {
-            let mut entry = self.wad.get(i).expect("valid entry");
-
-            match entry.name().as_str() {
-                "DS_START" => {
-                    in_sounds = true;
-                    continue;
-                }
-                "DS_END" => {
-                    in_sounds = false;
-                    continue;
-                }
-                // skip skin lump
-                "S_SKIN" => continue,
-                // skip if within sound bounds
-                _ if in_sounds => continue,
-                _ => (),
-            }
+    fn read_lump(&mut self, ix: usize) -> Result<Lump, Error> {
+        let mut entry = self.wad.get(ix).expect("valid entry");
+        let mut buf = Vec::with_capacity(entry.bytes_len());
+        entry.read_to_end(&mut buf)?;
+        Ok(Lump::new(buf))
+    }
+}
 
-            // read patch data
-            let mut buf = Vec::with_capacity(entry.bytes_len());
-            match entry.read_to_end(&mut buf) {
-                Ok(_) => (),
-                Err(err) => return Some(Err(err.into())),
-            }
-            if let Err(err) = index.add(*entry.name(), Lump::new(buf)) {
-                warn!("{:?}", err);
-            }
-        }
+impl Iterator for WadSkinLoader {
+    type Item = Result<Skin, Error>;
 
-        Some(Ok(Skin {
-            skin: Arc::new(skin_define),
-            index: Arc::new(index),
-        }))
+    fn next(&mut self) -> Option<Self::Item> {
+        let (s_skin, lumps) = self.skins.get(self.next).cloned()?;
+        self.next += 1;
+
+        Some(self.read_skin(s_skin, lumps))
     }
 
     fn size_hint(&self) -> (usize, Option<usize>) {
@@ -113,7 +111,98 @@ impl Iterator for WadSkinLoader {
 
 impl ExactSizeIterator for WadSkinLoader {
     fn len(&self) -> usize {
-        // wad files can only contain one char
-        1
+        self.skins.len() - self.next
+    }
+}
+
+/// Splits a lump directory into skins.
+///
+/// Returns the index of every `S_SKIN` lump, and the range of lumps that
+/// belong to it.
+fn skin_ranges(names: &[Name]) -> Vec<(usize, Range<usize>)> {
+    let is_marker = |name: &Name| {
+        ["S_SKIN", "S_START", "S_END"]
+            .iter()
+            .any(|marker| name.as_str().eq_ignore_ascii_case(marker))
+    };
+
+    names
+        .iter()
+        .enumerate()
+        .filter(|(_, name)| name.as_str().eq_ignore_ascii_case("S_SKIN"))
+        .map(|(i, _)| {
+            let start = i + 1;
+            let end = names[start..]
+                .iter()
+                .position(is_marker)
+                .map(|len| start + len)
+                .unwrap_or(names.len());
+            (i, start..end)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_skin_ranges() {
+        let names = [
+            "S_START", "S_SKIN", "STINA1", "STINA2", "S_SKIN", "STINA1", "S_END", "PLAYPAL",
+            "S_SKIN", "STINA1",
+        ]
+        .into_iter()
+        .map(|name| name.parse::<Name>().unwrap())
+        .collect::<Vec<_>>();
+
+        assert_eq!(skin_ranges(&names), &[(1, 2..4), (4, 5..6), (8, 9..10)]);
+    }
+
+    #[test]
+    fn test_wad_skins() {
+        const PATCH: &[u8] = include_bytes!("../../SPCNK0.lmp");
+
+        let wad = crate::testing::pwad(&[
+            ("SAKRANK", PATCH),
+            ("TAILRANK", PATCH),
+            (
+                "S_SKIN",
+                b"name = sakura\nrealname = Sakura\nprefcolor = Pink\nfacerank = SAKRANK\n",
+            ),
+            ("STINA1", PATCH),
+            // sounds are not sprites
+            ("DS_START", b""),
+            ("DSSAKWIN", b"not a patch"),
+            ("DS_END", b""),
+            (
+                "S_SKIN",
+                b"name = tails\nrealname = Tails\nprefcolor = Orange\nfacerank = TAILRANK\n",
+            ),
+            ("SPINA1", PATCH),
+        ]);
+
+        let skins = WadSkinLoader::new(wad)
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(skins.len(), 2);
+
+        let sakura = &skins[0];
+        assert_eq!(sakura.name, "sakura");
+        assert_eq!(
+            sakura.iter().collect::<Vec<_>>(),
+            &["STIN".parse::<Name>().unwrap()]
+        );
+        assert!(sakura.has_portrait(PortraitKind::Rank));
+        assert!(!sakura.has_portrait(PortraitKind::Wanted));
+
+        let tails = &skins[1];
+        assert_eq!(tails.name, "tails");
+        assert_eq!(
+            tails.iter().collect::<Vec<_>>(),
+            &["SPIN".parse::<Name>().unwrap()]
+        );
+        assert!(tails.portrait(PortraitKind::Rank).is_ok());
     }
 }
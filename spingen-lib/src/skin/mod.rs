@@ -1,10 +1,13 @@
 //! Discovers and compiles all skins.
 
+pub mod catalogue;
 pub mod loaders;
+pub mod portrait;
 pub mod spr2;
 
 use crate::doom::{patch::Patch, skin::SkinDefine};
 use crate::Error;
+use portrait::{PortraitKind, Portraits};
 use spr2::Spr2;
 
 use std::fmt::{self, Debug, Formatter};
@@ -15,72 +18,54 @@ use wad::Name;
 
 use derive_more::{Deref, Display};
 
-use wasm_bindgen::prelude::*;
-
 /// The actual internal skin data.
 ///
 /// Contains information about the skin, and all the patches associated with
 /// it. This data never changes, so it is exchanged around in an [`Arc`].
 #[derive(Clone, Deref)]
-#[wasm_bindgen]
 pub struct Skin {
     /// The skin index
     index: Arc<spr2::Index>,
+    /// The portrait graphics.
+    portraits: Arc<Portraits>,
     /// The skin description.
     #[deref]
     skin: Arc<SkinDefine>,
 }
 
-#[wasm_bindgen]
 impl Skin {
-    #[wasm_bindgen(getter)]
-    pub fn name(&self) -> String {
-        self.skin.name.clone()
-    }
-
-    #[wasm_bindgen(getter)]
-    pub fn realname(&self) -> String {
-        self.skin.realname.clone()
+    /// Reads a patch from the skin.
+    pub fn read(&self, name: &Name) -> Result<Patch, Error> {
+        self.index.read(name)
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn kartspeed(&self) -> i32 {
-        self.skin.kartspeed
+    /// Reads one of the skin's portraits.
+    pub fn portrait(&self, kind: PortraitKind) -> Result<Patch, Error> {
+        self.portraits.read(kind.lump_name(self))
     }
 
-    #[wasm_bindgen(getter)]
-    pub fn kartweight(&self) -> i32 {
-        self.skin.kartweight
+    /// Checks if the skin has a portrait.
+    ///
+    /// The game draws a placeholder for missing portraits.
+    pub fn has_portrait(&self, kind: PortraitKind) -> bool {
+        self.portraits.contains(kind.lump_name(self))
     }
 
-    pub fn sprites(&self) -> Vec<String> {
-        let mut sprites = self
-            .iter()
-            .map(|name| name.as_str().to_owned())
-            .collect::<Vec<_>>();
-        sprites.sort();
+    /// All unique skin sprite names, in [catalogue](catalogue::CATALOGUE)
+    /// order.
+    ///
+    /// Sprites the catalogue doesn't know about come last, alphabetically.
+    pub fn sprites(&self) -> Vec<Name> {
+        let mut sprites = self.iter().collect::<Vec<_>>();
+        sprites.sort_by_key(|name| (catalogue::Spr2Info::position(name), *name));
         sprites
     }
 
-    pub fn frames(&self, frame: String) -> Result<Vec<String>, JsValue> {
-        let name = frame
-            .parse::<Name>()
-            .map_err(|err| JsValue::from(format!("{}", err)))?;
-
-        let mut frames = self
-            .iter_frames(&name)
-            .map(|ch| ch as char)
-            .map(String::from)
-            .collect::<Vec<_>>();
-        frames.sort();
-        Ok(frames)
-    }
-}
-
-impl Skin {
-    /// Reads a patch from the skin.
-    pub fn read(&self, name: &Name) -> Result<Patch, Error> {
-        self.index.read(name)
+    /// Resolves a sprite to the one the game would actually draw.
+    ///
+    /// See [`spr2::Index::resolve`].
+    pub fn resolve(&self, name: &Name) -> Option<spr2::Resolved> {
+        self.index.resolve(name)
     }
 
     /// Iterates over all unique skin sprite names.
@@ -89,7 +74,7 @@ impl Skin {
     }
 
     /// Iterates over all unique frames of a sprite.
-    pub fn iter_frames(&self, name: &Name) -> impl Iterator<Item = u8> {
+    pub fn iter_frames(&self, name: &Name) -> impl Iterator<Item = FrameNumber> {
         self.index.iter_frames(name)
     }
 
@@ -97,10 +82,20 @@ impl Skin {
     pub fn iter_angles<'a>(
         &'a self,
         name: &'a Name,
-        frame: u8,
+        frame: FrameNumber,
     ) -> impl Iterator<Item = &'a Spr2> + 'a {
         self.index.iter_angles(name, frame)
     }
+
+    /// Checks if a sprite frame has a 16-angle set.
+    pub fn is_16_angle(&self, name: &Name, frame: FrameNumber) -> bool {
+        self.index.is_16_angle(name, frame)
+    }
+
+    /// Gets a single sprite frame at an angle.
+    pub fn get(&self, name: &Name, frame: FrameNumber, angle: SpriteAngle) -> Option<&Spr2> {
+        self.index.get(name, frame, angle)
+    }
 }
 
 impl Debug for Skin {
@@ -135,6 +130,15 @@ impl SpriteName {
     pub fn mirrored_frame(&self) -> Option<&SpriteFrame> {
         self.mirrored_frame.as_ref()
     }
+
+    /// Checks if the sprite belongs to a 16-angle set.
+    pub fn is_16_angle(&self) -> bool {
+        self.frame.angle.is_16_angle()
+            || self
+                .mirrored_frame
+                .map(|f| f.angle.is_16_angle())
+                .unwrap_or_default()
+    }
 }
 
 impl TryFrom<Name> for SpriteName {
@@ -143,13 +147,12 @@ impl TryFrom<Name> for SpriteName {
     fn try_from(value: Name) -> Result<Self, Self::Error> {
         if value.as_str().len() >= 6 {
             let to_frame = |bytes: &[u8]| {
-                if bytes[0] < b'A' {
+                let Some(frame) = FrameNumber::from_ascii_char(bytes[0]) else {
                     return Err(FromNameError {
                         name: value,
                         kind: FromNameErrorKind::InvalidFrame(bytes[0] as char),
                     });
-                }
-                let frame = bytes[0];
+                };
 
                 let Some(angle) = SpriteAngle::from_ascii_char(bytes[1]) else {
                     return Err(FromNameError {
@@ -185,12 +188,83 @@ impl TryFrom<Name> for SpriteName {
 /// A frame of a sprite.
 #[derive(Clone, Copy, Debug)]
 pub struct SpriteFrame {
-    pub frame: u8,
+    pub frame: FrameNumber,
     pub angle: SpriteAngle,
 }
 
-/// A sprite angle.
+/// A frame number of a sprite.
+///
+/// Frames are written as a single character in sprite names. The game maps
+/// `A` to `Z` to frames 0 to 25, `0` to `9` to frames 26 to 35, `a` to `z`
+/// to frames 36 to 61, and `!` and `@` to frames 62 and 63.
+///
+/// Frames are ordered by frame number, which is the order the game plays
+/// them back in.
 #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrameNumber(u8);
+
+impl FrameNumber {
+    /// The first frame, `A`.
+    pub const A: FrameNumber = FrameNumber(0);
+    /// The highest frame number.
+    pub const MAX: FrameNumber = FrameNumber(63);
+
+    /// Creates a `FrameNumber` from a frame number.
+    ///
+    /// Returns `None` if the number is out of range.
+    pub fn new(number: u8) -> Option<FrameNumber> {
+        (number <= FrameNumber::MAX.0).then_some(FrameNumber(number))
+    }
+
+    /// Unwraps the frame number.
+    pub fn into_inner(self) -> u8 {
+        self.0
+    }
+
+    /// Creates a `FrameNumber` from an ascii char.
+    ///
+    /// Returns `None` if the frame is invalid.
+    pub fn from_ascii_char(byte: u8) -> Option<FrameNumber> {
+        let number = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'0'..=b'9' => byte - b'0' + 26,
+            b'a'..=b'z' => byte - b'a' + 36,
+            b'!' => 62,
+            b'@' => 63,
+            _ => return None,
+        };
+
+        Some(FrameNumber(number))
+    }
+
+    /// The ascii char of the frame, as written in sprite names.
+    pub fn to_ascii_char(self) -> u8 {
+        match self.0 {
+            number @ 0..=25 => b'A' + number,
+            number @ 26..=35 => b'0' + number - 26,
+            number @ 36..=61 => b'a' + number - 36,
+            62 => b'!',
+            _ => b'@',
+        }
+    }
+}
+
+impl fmt::Display for FrameNumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.to_ascii_char() as char)
+    }
+}
+
+/// A sprite angle.
+///
+/// Sprites come in sets of either 8 or 16 angles. The 8-angle set uses the
+/// characters `1` to `8`, going around the sprite from the front. 16-angle
+/// sets add the characters `9` and `A` to `G` in between them, so `9` sits
+/// between `1` and `2`, `A` between `2` and `3`, and `G` between `8` and `1`.
+///
+/// Angles are ordered the way they go around the sprite, so sorting a set
+/// of angles gives a turnaround.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct SpriteAngle(NonZeroU8);
 
 impl SpriteAngle {
@@ -222,12 +296,87 @@ impl SpriteAngle {
     ///
     /// Returns `None` if the angle is invalid.
     pub fn from_ascii_char(byte: u8) -> Option<SpriteAngle> {
-        if (b'0'..=b'9').contains(&byte) {
+        if matches!(byte, b'0'..=b'9' | b'A'..=b'G') {
             Some(SpriteAngle(NonZeroU8::new(byte).expect("valid byte")))
         } else {
             None
         }
     }
+
+    /// The rotation number of the angle.
+    ///
+    /// This is `0` for [`SpriteAngle::ALL`], `1` to `8` for the 8-angle set
+    /// and `9` to `16` for the angles only in 16-angle sets.
+    pub fn rotation(self) -> u8 {
+        match self.0.get() {
+            byte @ b'0'..=b'9' => byte - b'0',
+            byte => byte - b'A' + 10,
+        }
+    }
+
+    /// Checks if the angle only exists in 16-angle sets.
+    pub fn is_16_angle(self) -> bool {
+        self.rotation() > 8
+    }
+
+    /// The angle the game shows instead on a sprite with only 8 angles.
+    ///
+    /// In-between angles round to the next angle around the sprite, so `9`
+    /// becomes `2` and `G` becomes `1`.
+    pub fn to_8_angle(self) -> SpriteAngle {
+        if self.is_16_angle() {
+            let rotation = self.rotation() - 8;
+            let rotation = rotation % 8 + 1;
+            SpriteAngle::from_ascii_char(b'0' + rotation).expect("valid angle")
+        } else {
+            self
+        }
+    }
+
+    /// The angle on the other side of the sprite, as seen in a mirror.
+    ///
+    /// The front and back angles, [`SpriteAngle::FORWARD`],
+    /// [`SpriteAngle::BACKWARD`] and [`SpriteAngle::ALL`], mirror to
+    /// themselves.
+    pub fn mirror(self) -> SpriteAngle {
+        // mirrors an 8-angle rotation
+        let mirror_8 = |rotation: u8| (8 - (rotation - 1)) % 8 + 1;
+
+        let rotation = match self.rotation() {
+            0 => 0,
+            rotation @ 1..=8 => mirror_8(rotation),
+            // the in-between angle after k is before k's mirror
+            rotation => mirror_8((rotation - 8) % 8 + 1) + 8,
+        };
+
+        if rotation < 10 {
+            SpriteAngle::from_ascii_char(b'0' + rotation)
+        } else {
+            SpriteAngle::from_ascii_char(b'A' + rotation - 10)
+        }
+        .expect("valid angle")
+    }
+
+    /// The position of the angle going around the sprite.
+    fn turn(self) -> u8 {
+        match self.rotation() {
+            0 => 0,
+            rotation @ 1..=8 => (rotation - 1) * 2 + 1,
+            rotation => (rotation - 9) * 2 + 2,
+        }
+    }
+}
+
+impl PartialOrd for SpriteAngle {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for SpriteAngle {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.turn().cmp(&other.turn())
+    }
 }
 
 /// A patch with a name.
@@ -263,7 +412,7 @@ impl Sprite {
     ///
     /// The boolean returned will be `true` if the sprite must be mirrored to
     /// produce the angle.
-    pub fn provides(&self, frame: u8, angle: SpriteAngle) -> Option<bool> {
+    pub fn provides(&self, frame: FrameNumber, angle: SpriteAngle) -> Option<bool> {
         // terrible naming choices were made
         let f = self.name.frame();
         if (f.angle == angle || f.angle == SpriteAngle::ALL) && f.frame == frame {
@@ -0,0 +1,113 @@
+//! Skin portraits.
+//!
+//! Besides its sprites, a skin names three graphics in its `S_SKIN` that the
+//! game draws on the HUD: the rank icon, the Wanted icon and the minimap icon.
+//! These are plain patches looked up by name, so they are kept apart from the
+//! [sprite index](super::spr2::Index).
+
+use std::io::Cursor;
+use std::str::FromStr;
+
+use ahash::HashMap;
+
+use derive_more::Display;
+
+use wad::Name;
+
+use crate::doom::patch::{Palette, Patch};
+use crate::doom::skin::SkinDefine;
+use crate::lump::Lump;
+use crate::Error;
+
+use super::spr2::load_image;
+
+/// One of the portraits of a skin.
+#[derive(Clone, Copy, Debug, Display, PartialEq, Eq, Hash)]
+pub enum PortraitKind {
+    /// The icon next to the racer's position, `facerank`.
+    #[display("rank")]
+    Rank,
+    /// The icon on the Wanted list, `facewant`.
+    #[display("wanted")]
+    Wanted,
+    /// The icon on the minimap, `facemmap`.
+    #[display("minimap")]
+    Minimap,
+}
+
+impl PortraitKind {
+    /// Every kind of portrait.
+    pub const ALL: [PortraitKind; 3] = [
+        PortraitKind::Rank,
+        PortraitKind::Wanted,
+        PortraitKind::Minimap,
+    ];
+
+    /// The name of the graphic a skin define uses for this portrait.
+    pub fn lump_name(self, define: &SkinDefine) -> &str {
+        match self {
+            PortraitKind::Rank => &define.facerank,
+            PortraitKind::Wanted => &define.facewant,
+            PortraitKind::Minimap => &define.facemmap,
+        }
+    }
+}
+
+impl FromStr for PortraitKind {
+    type Err = InvalidPortraitKind;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        PortraitKind::ALL
+            .into_iter()
+            .find(|kind| kind.to_string().eq_ignore_ascii_case(s))
+            .ok_or_else(|| InvalidPortraitKind(s.to_owned()))
+    }
+}
+
+/// An error for parsing a [`PortraitKind`].
+#[derive(Debug, Display, Error)]
+#[display("invalid portrait \"{_0}\"")]
+pub struct InvalidPortraitKind(#[error(not(source))] pub String);
+
+/// The portrait graphics of a skin, by lump name.
+#[derive(Clone, Debug, Default)]
+pub struct Portraits {
+    lumps: HashMap<Name, Lump>,
+}
+
+impl Portraits {
+    /// Checks if a lump is one of the portraits of a skin define.
+    pub fn is_portrait(define: &SkinDefine, name: &Name) -> bool {
+        PortraitKind::ALL
+            .into_iter()
+            .any(|kind| kind.lump_name(define).eq_ignore_ascii_case(name.as_str()))
+    }
+
+    /// Adds a portrait graphic.
+    pub fn add(&mut self, name: Name, lump: Lump) {
+        self.lumps.insert(name, lump);
+    }
+
+    /// Checks if a portrait graphic exists.
+    pub fn contains(&self, name: &str) -> bool {
+        self.find(name).is_some()
+    }
+
+    /// Reads a portrait graphic.
+    pub fn read(&self, name: &str) -> Result<Patch, Error> {
+        let lump = self
+            .find(name)
+            .ok_or_else(|| Error::NotFound(name.to_owned()))?;
+
+        let bytes = lump.clone().read()?;
+        load_image(Cursor::new(bytes), &Palette::default())
+            .map_err(|err| Error::Image(name.to_owned(), err))
+    }
+
+    fn find(&self, name: &str) -> Option<&Lump> {
+        self.lumps
+            .iter()
+            .find(|(lump_name, _)| lump_name.as_str().eq_ignore_ascii_case(name))
+            .map(|(_, lump)| lump)
+    }
+}
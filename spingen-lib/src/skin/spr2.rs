@@ -7,10 +7,11 @@ use ahash::{HashMap, HashSet};
 
 use wad::Name;
 
-use crate::doom::patch::{Palette, Patch};
+use crate::doom::patch::{Palette, Patch, PALETTE_COLORS};
 use crate::lump::Lump;
 
-use super::{Error, FromNameError, FromNameErrorKind, SpriteAngle};
+use super::catalogue::Spr2Info;
+use super::{Error, FrameNumber, FromNameError, FromNameErrorKind, SpriteAngle};
 
 use std::io::{Cursor, Read, Seek, SeekFrom};
 
@@ -48,7 +49,13 @@ impl Index {
         let identifier = Name::from_bytes(&name[..4]).expect("valid subname");
 
         // get default sprite
-        let frame = name[4];
+        let Some(frame) = FrameNumber::from_ascii_char(name[4]) else {
+            return Err(FromNameError {
+                name,
+                kind: FromNameErrorKind::InvalidFrame(name[4] as char),
+            }
+            .into());
+        };
         let Some(angle) = SpriteAngle::from_ascii_char(name[5]) else {
             return Err(FromNameError {
                 name,
@@ -59,7 +66,13 @@ impl Index {
 
         // get mirror sprite
         if name.as_str().len() >= 8 {
-            let frame = name[6];
+            let Some(frame) = FrameNumber::from_ascii_char(name[6]) else {
+                return Err(FromNameError {
+                    name,
+                    kind: FromNameErrorKind::InvalidFrame(name[6] as char),
+                }
+                .into());
+            };
             let Some(angle) = SpriteAngle::from_ascii_char(name[7]) else {
                 return Err(FromNameError {
                     name,
@@ -99,6 +112,32 @@ impl Index {
             })
     }
 
+    /// Checks if the index has any frames of a sprite.
+    pub fn contains(&self, name: &Name) -> bool {
+        self.sprites.values().any(|spr| spr.index.name == *name)
+    }
+
+    /// Resolves a sprite to the one the game would actually draw.
+    ///
+    /// Like the game, a missing sprite falls back to its parent in the
+    /// [`SPR2_DEFAULTS`] graph until one is found, eventually reaching `STIN`.
+    /// Returns `None` if neither the sprite nor any of its parents exist, or if
+    /// the sprite isn't in the [catalogue](super::catalogue::CATALOGUE).
+    pub fn resolve(&self, name: &Name) -> Option<Resolved> {
+        let mut current = *name;
+
+        loop {
+            if self.contains(&current) {
+                return Some(Resolved {
+                    requested: *name,
+                    name: current,
+                });
+            }
+
+            current = spr2_default(&current)?;
+        }
+    }
+
     /// Iterates over all the unique sprite names.
     pub fn iter(&self) -> impl Iterator<Item = Name> {
         self.sprites
@@ -109,7 +148,7 @@ impl Index {
     }
 
     /// Iterates over all the frames of a sprite.
-    pub fn iter_frames(&self, name: &Name) -> impl Iterator<Item = u8> {
+    pub fn iter_frames(&self, name: &Name) -> impl Iterator<Item = FrameNumber> {
         self.sprites
             .values()
             .filter(|spr| spr.index.name == *name)
@@ -122,15 +161,141 @@ impl Index {
     pub fn iter_angles<'a>(
         &'a self,
         name: &'a Name,
-        frame: u8,
+        frame: FrameNumber,
     ) -> impl Iterator<Item = &'a Spr2> + 'a {
         self.sprites
             .values()
             .filter(move |spr| spr.index.name == *name && spr.index.frame == frame)
     }
 
+    /// Checks if a sprite frame has a 16-angle set.
+    ///
+    /// Like the game, a frame counts as 16-angle as soon as it has any of the
+    /// in-between angles.
+    pub fn is_16_angle(&self, name: &Name, frame: FrameNumber) -> bool {
+        self.iter_angles(name, frame)
+            .any(|spr| spr.index.angle.is_16_angle())
+    }
+
+    /// Gets a single sprite frame at an angle.
+    ///
+    /// Frames drawn for every angle at once, with the `0` angle, are returned
+    /// for any angle. Asking a frame with only 8 angles for an in-between
+    /// angle gets the angle the game would show instead.
+    pub fn get(&self, name: &Name, frame: FrameNumber, angle: SpriteAngle) -> Option<&Spr2> {
+        let angle = if self.is_16_angle(name, frame) {
+            angle
+        } else {
+            angle.to_8_angle()
+        };
+
+        self.sprites
+            .get(&SpriteIndex::new(*name, frame, angle))
+            .or_else(|| {
+                self.sprites
+                    .get(&SpriteIndex::new(*name, frame, SpriteAngle::ALL))
+            })
+    }
+
     fn insert(&mut self, spr2: Spr2) {
         self.sprites.insert(spr2.index, spr2);
+
+        // fill in the opposite angle by mirroring, like the game does, unless
+        // it was authored
+        let angle = spr2.index.angle.mirror();
+        if angle == spr2.index.angle {
+            return;
+        }
+
+        let index = SpriteIndex {
+            angle,
+            ..spr2.index
+        };
+        let authored = self
+            .sprites
+            .get(&index)
+            .map(|spr| !spr.synthesised)
+            .unwrap_or_default();
+        if !authored {
+            self.sprites.insert(
+                index,
+                Spr2 {
+                    index,
+                    name: spr2.name,
+                    mirror: !spr2.mirror,
+                    synthesised: true,
+                },
+            );
+        }
+    }
+}
+
+/// The parents of each player sprite, like the game's `spr2defaults`.
+///
+/// Catalogue sprites without an entry fall back to `STIN`, like a
+/// `spr2defaults` entry of 0 in the game.
+pub const SPR2_DEFAULTS: &[(&str, &str)] = &[
+    ("STIL", "STIN"),
+    ("STIR", "STIN"),
+    ("STGL", "STIN"),
+    ("STGR", "STIN"),
+    ("STLL", "STGL"),
+    ("STLR", "STGR"),
+    ("SLWN", "STIN"),
+    ("SLWL", "SLWN"),
+    ("SLWR", "SLWN"),
+    ("SLGL", "SLWN"),
+    ("SLGR", "SLWN"),
+    ("SLLL", "SLGL"),
+    ("SLLR", "SLGR"),
+    ("FSTN", "SLWN"),
+    ("FSTL", "FSTN"),
+    ("FSTR", "FSTN"),
+    ("FSGL", "FSTN"),
+    ("FSGR", "FSTN"),
+    ("FSLL", "FSGL"),
+    ("FSLR", "FSGR"),
+    ("DRLN", "FSTN"),
+    ("DRLO", "DRLN"),
+    ("DRLI", "DRLN"),
+    ("DRRN", "FSTN"),
+    ("DRRO", "DRRN"),
+    ("DRRI", "DRRN"),
+    ("SPIN", "STIN"),
+    ("DEAD", "SPIN"),
+    ("SIGL", "SIGN"),
+    ("SSIG", "SIGN"),
+];
+
+/// Gets the parent of a player sprite.
+fn spr2_default(name: &Name) -> Option<Name> {
+    let parent = SPR2_DEFAULTS
+        .iter()
+        .find(|(child, _)| child.as_bytes().eq_ignore_ascii_case(name))
+        .map(|(_, parent)| *parent);
+
+    let parent = match parent {
+        Some(parent) => parent,
+        None if b"STIN".eq_ignore_ascii_case(name) => return None,
+        None => Spr2Info::get(name).map(|_| "STIN")?,
+    };
+
+    Some(Name::from_bytes(parent.as_bytes()).expect("valid name"))
+}
+
+/// A sprite name resolved through the SPR2 fallback chain.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Resolved {
+    /// The sprite that was asked for.
+    pub requested: Name,
+    /// The sprite the game would draw.
+    pub name: Name,
+}
+
+impl Resolved {
+    /// Checks if the requested sprite was missing, and a parent was used.
+    pub fn is_fallback(&self) -> bool {
+        self.requested != self.name
     }
 }
 
@@ -140,14 +305,14 @@ pub struct SpriteIndex {
     /// The base name of the sprite.
     pub name: Name,
     /// The frame.
-    pub frame: u8,
+    pub frame: FrameNumber,
     /// The angle.
     pub angle: SpriteAngle,
 }
 
 impl SpriteIndex {
     /// Creates a new `FrameIndex`.
-    pub fn new(name: Name, frame: u8, angle: SpriteAngle) -> SpriteIndex {
+    pub fn new(name: Name, frame: FrameNumber, angle: SpriteAngle) -> SpriteIndex {
         SpriteIndex { name, frame, angle }
     }
 }
@@ -160,6 +325,9 @@ pub struct Spr2 {
     pub name: Name,
     /// Whether to mirror the graphic to produce the final sprite.
     pub mirror: bool,
+    /// Whether the angle is missing from the skin, and was filled in by
+    /// mirroring the opposite angle.
+    pub synthesised: bool,
 }
 
 impl Spr2 {
@@ -169,6 +337,7 @@ impl Spr2 {
             index,
             name,
             mirror,
+            synthesised: false,
         }
     }
 }
@@ -189,56 +358,420 @@ where
 }
 
 /// Attempts to load a truecolor image as a patch.
+///
+/// The offsets of the patch are taken from the `grAb` chunk of the PNG, like
+/// in Ring Racers. PNGs without it get `0, 0` offsets.
 pub fn load_truecolor<R>(mut reader: R, palette: &Palette) -> Result<Patch, Report>
 where
     R: Read,
 {
+    let mut buf = Vec::new();
+    reader.read_to_end(&mut buf)?;
+
+    let (left, top) = read_grab(&buf).unwrap_or_default();
+
     // decode png
     let limits = png::Limits::default();
-    let png = png::Decoder::new_with_limits(&mut reader, limits);
+    let mut png = png::Decoder::new_with_limits(Cursor::new(&buf), limits);
+
+    // paletted images are kept as indices, so they can be mapped directly
+    let indexed = png.read_header_info()?.color_type == png::ColorType::Indexed;
+    if indexed {
+        png.set_transformations(png::Transformations::IDENTITY);
+    } else {
+        png.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    }
+
     let mut reader = png.read_info()?;
 
     let mut frame_buf = vec![0u8; reader.output_buffer_size()];
     let info = reader.next_frame(&mut frame_buf)?;
 
-    let color_type = info.color_type;
     let width = u16::try_from(info.width).wrap_err("image width out of bounds")?;
     let height = u16::try_from(info.height).wrap_err("image height out of bounds")?;
-
-    let data = match color_type {
-        png::ColorType::Rgb => (0..width as usize * height as usize)
-            .map(|i| &frame_buf[i * 3..i * 3 + 3])
-            .map(|color| {
-                let mut buf = [u8::MAX; 4];
-                buf[..3].copy_from_slice(color);
-
-                let color: Color = Srgba::from_u8_array(buf).into();
+    let len = width as usize * height as usize;
+
+    // remember already matched colors, finding the nearest color is slow
+    let mut colors = HashMap::<[u8; 4], Option<u8>>::default();
+    let mut to_palette = |color: [u8; 4]| {
+        *colors.entry(color).or_insert_with(|| {
+            // mask alpha
+            if color[3] < 128 {
+                None
+            } else {
+                let color: Color = Srgba::from_u8_array(color).into();
                 Some(palette.nearest_color(color) as u8)
-            })
+            }
+        })
+    };
+
+    let data = match info.color_type {
+        png::ColorType::Indexed => {
+            let png_info = reader.info();
+            let Some(plte) = png_info.palette.as_deref() else {
+                return Err(Report::msg("indexed image has no palette"));
+            };
+            let trns = png_info.trns.as_deref().unwrap_or_default();
+
+            // if the palettes are the same, the indices can be used as-is
+            let same_palette = plte.len() <= PALETTE_COLORS * 3
+                && plte
+                    .chunks_exact(3)
+                    .enumerate()
+                    .all(|(i, color)| palette[i].to_srgba().to_u8_array()[..3] == color[..]);
+
+            let lookup = plte
+                .chunks_exact(3)
+                .enumerate()
+                .map(|(i, color)| {
+                    let alpha = trns.get(i).copied().unwrap_or(u8::MAX);
+
+                    if same_palette {
+                        (alpha >= 128).then_some(i as u8)
+                    } else {
+                        to_palette([color[0], color[1], color[2], alpha])
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            let bits = info.bit_depth as usize;
+            let mask = ((1u16 << bits) - 1) as u8;
+
+            (0..len)
+                .map(|i| {
+                    let (x, y) = (i % width as usize, i / width as usize);
+
+                    // indices can be packed into less than a byte
+                    let bit = x * bits;
+                    let byte = frame_buf[y * info.line_size + bit / 8];
+                    let ix = (byte >> (8 - bits - bit % 8)) & mask;
+
+                    lookup.get(ix as usize).copied().flatten()
+                })
+                .collect::<Vec<_>>()
+        }
+        png::ColorType::Grayscale => (0..len)
+            .map(|i| frame_buf[i])
+            .map(|v| to_palette([v, v, v, u8::MAX]))
+            .collect::<Vec<_>>(),
+        png::ColorType::GrayscaleAlpha => (0..len)
+            .map(|i| &frame_buf[i * 2..i * 2 + 2])
+            .map(|color| to_palette([color[0], color[0], color[0], color[1]]))
             .collect::<Vec<_>>(),
-        png::ColorType::Rgba => (0..width as usize * height as usize)
+        png::ColorType::Rgb => (0..len)
             .map(|i| &frame_buf[i * 3..i * 3 + 3])
-            .map(|color| {
-                let mut buf = [0u8; 4];
-                buf.copy_from_slice(color);
-
-                // mask alpha
-                if buf[3] < 128 {
-                    None
-                } else {
-                    let color: Color = Srgba::from_u8_array(buf).into();
-                    Some(palette.nearest_color(color) as u8)
-                }
-            })
+            .map(|color| to_palette([color[0], color[1], color[2], u8::MAX]))
+            .collect::<Vec<_>>(),
+        png::ColorType::Rgba => (0..len)
+            .map(|i| &frame_buf[i * 4..i * 4 + 4])
+            .map(|color| to_palette([color[0], color[1], color[2], color[3]]))
             .collect::<Vec<_>>(),
-        _ => todo!(),
     };
 
     Ok(Patch {
-        left: 0,
-        top: 0,
+        left,
+        top,
         width,
         height,
         data,
     })
 }
+
+/// The PNG signature.
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Finds the `grAb` chunk of a PNG, returning the offsets in it.
+///
+/// The `png` crate skips over chunks it doesn't know about, so this walks the
+/// chunks itself. Returns `None` if the PNG is malformed or has no `grAb`.
+fn read_grab(buf: &[u8]) -> Option<(i32, i32)> {
+    let mut buf = buf.strip_prefix(PNG_SIGNATURE)?;
+
+    // each chunk is a length, a type, the data, then a crc
+    while buf.len() >= 8 {
+        let len = u32::from_be_bytes(buf[..4].try_into().ok()?) as usize;
+        let kind = &buf[4..8];
+        let data = buf.get(8..8 + len)?;
+
+        match kind {
+            b"grAb" if len == 8 => {
+                let x = i32::from_be_bytes(data[..4].try_into().ok()?);
+                let y = i32::from_be_bytes(data[4..].try_into().ok()?);
+                return Some((x, y));
+            }
+            // grAb must come before the image data
+            b"IDAT" | b"IEND" => return None,
+            _ => (),
+        }
+
+        buf = buf.get(12 + len..)?;
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_16_angles() {
+        let mut index = Index::new();
+        for name in [
+            "STINA1", "STINA9", "STINA2A8", "STINAAAG", "STINB1", "STINB2B8",
+        ] {
+            index
+                .add(name.parse().unwrap(), Lump::new(Vec::new()))
+                .unwrap();
+        }
+
+        let stin = "STIN".parse::<Name>().unwrap();
+        let (a, b) = (FrameNumber::A, FrameNumber::from_ascii_char(b'B').unwrap());
+        assert!(index.is_16_angle(&stin, a));
+        assert!(!index.is_16_angle(&stin, b));
+
+        // turnarounds go around the sprite
+        let mut angles = index
+            .iter_angles(&stin, a)
+            .map(|spr| spr.index.angle.into_inner() as char)
+            .collect::<Vec<_>>();
+        angles.sort_by_key(|ch| SpriteAngle::from_ascii_char(*ch as u8));
+        // F is mirrored from A
+        assert_eq!(angles, &['1', '9', '2', 'A', 'F', '8', 'G']);
+
+        // 8-angle frames fall back to the angle the game shows
+        let angle = SpriteAngle::from_ascii_char(b'9').unwrap();
+        assert_eq!(index.get(&stin, a, angle).unwrap().name.as_str(), "STINA9");
+        assert_eq!(
+            index.get(&stin, b, angle).unwrap().name.as_str(),
+            "STINB2B8"
+        );
+        let angle = SpriteAngle::from_ascii_char(b'G').unwrap();
+        assert_eq!(index.get(&stin, b, angle).unwrap().name.as_str(), "STINB1");
+    }
+
+    #[test]
+    fn test_mirror_fill() {
+        let mut index = Index::new();
+        for name in ["STINA1", "STINA2", "STINA3", "STINA4A6", "STINA5", "STINA7"] {
+            index
+                .add(name.parse().unwrap(), Lump::new(Vec::new()))
+                .unwrap();
+        }
+
+        let stin = "STIN".parse::<Name>().unwrap();
+        let mut angles = index
+            .iter_angles(&stin, FrameNumber::A)
+            .map(|spr| {
+                (
+                    spr.index.angle.into_inner() as char,
+                    spr.name.to_string(),
+                    spr.mirror,
+                    spr.synthesised,
+                )
+            })
+            .collect::<Vec<_>>();
+        angles.sort();
+
+        assert_eq!(
+            angles,
+            &[
+                ('1', "STINA1".into(), false, false),
+                ('2', "STINA2".into(), false, false),
+                ('3', "STINA3".into(), false, false),
+                ('4', "STINA4A6".into(), false, false),
+                ('5', "STINA5".into(), false, false),
+                ('6', "STINA4A6".into(), true, false),
+                ('7', "STINA7".into(), false, false),
+                ('8', "STINA2".into(), true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_resolve() {
+        let mut index = Index::new();
+        for name in ["STINA1", "FSTNA1"] {
+            index
+                .add(name.parse().unwrap(), Lump::new(Vec::new()))
+                .unwrap();
+        }
+
+        let resolve = |name: &str| {
+            index
+                .resolve(&name.parse().unwrap())
+                .map(|resolved| (resolved.name.to_string(), resolved.is_fallback()))
+        };
+
+        assert_eq!(resolve("STIN"), Some(("STIN".into(), false)));
+        assert_eq!(resolve("STLL"), Some(("STIN".into(), true)));
+        assert_eq!(resolve("DRLO"), Some(("FSTN".into(), true)));
+        assert_eq!(resolve("SIGN"), Some(("STIN".into(), true)));
+        assert_eq!(resolve("SIGL"), Some(("STIN".into(), true)));
+        assert_eq!(resolve("TALK"), Some(("STIN".into(), true)));
+        assert_eq!(resolve("ZZZZ"), None);
+    }
+
+    #[test]
+    fn test_frame_numbers() {
+        let mut index = Index::new();
+        for name in ["STINA1", "STIN01", "STINa1", "STIN!1", "STIN@1", "STINZ1"] {
+            index
+                .add(
+                    Name::from_bytes(name.as_bytes()).unwrap(),
+                    Lump::new(Vec::new()),
+                )
+                .unwrap();
+        }
+
+        let stin = "STIN".parse::<Name>().unwrap();
+        let mut frames = index.iter_frames(&stin).collect::<Vec<_>>();
+        frames.sort();
+        assert_eq!(
+            frames
+                .iter()
+                .map(|frame| frame.into_inner())
+                .collect::<Vec<_>>(),
+            &[0, 25, 26, 36, 62, 63]
+        );
+        assert_eq!(
+            frames
+                .iter()
+                .map(|frame| frame.to_ascii_char() as char)
+                .collect::<String>(),
+            "AZ0a!@"
+        );
+
+        assert!(index
+            .add("STIN#1".parse().unwrap(), Lump::new(Vec::new()))
+            .is_err());
+    }
+
+    fn encode_png(grab: Option<(i32, i32)>) -> Vec<u8> {
+        encode_png_with(
+            grab,
+            png::ColorType::Rgb,
+            png::BitDepth::Eight,
+            |_| (),
+            &[255; 2 * 2 * 3],
+        )
+    }
+
+    fn encode_png_with(
+        grab: Option<(i32, i32)>,
+        color: png::ColorType,
+        depth: png::BitDepth,
+        setup: impl FnOnce(&mut png::Encoder<&mut Vec<u8>>),
+        image: &[u8],
+    ) -> Vec<u8> {
+        let mut buf = Vec::new();
+
+        let mut encoder = png::Encoder::new(&mut buf, 2, 2);
+        encoder.set_color(color);
+        encoder.set_depth(depth);
+        setup(&mut encoder);
+
+        let mut writer = encoder.write_header().unwrap();
+        if let Some((x, y)) = grab {
+            let mut data = Vec::new();
+            data.extend(x.to_be_bytes());
+            data.extend(y.to_be_bytes());
+            writer
+                .write_chunk(png::chunk::ChunkType(*b"grAb"), &data)
+                .unwrap();
+        }
+        writer.write_image_data(image).unwrap();
+        writer.finish().unwrap();
+
+        buf
+    }
+
+    #[test]
+    fn test_grab() {
+        let palette = Palette::default();
+
+        let patch = load_truecolor(Cursor::new(encode_png(Some((-24, 48)))), &palette).unwrap();
+        assert_eq!((patch.left, patch.top), (-24, 48));
+
+        let patch = load_truecolor(Cursor::new(encode_png(None)), &palette).unwrap();
+        assert_eq!((patch.left, patch.top), (0, 0));
+    }
+
+    #[test]
+    fn test_color_types() {
+        let palette = Palette::default();
+        let white = Some(palette.nearest_color(Color::WHITE) as u8);
+        let black = Some(palette.nearest_color(Color::BLACK) as u8);
+
+        let load = |png: Vec<u8>| load_truecolor(Cursor::new(png), &palette).unwrap().data;
+
+        // grayscale, 16-bit
+        let png = encode_png_with(
+            None,
+            png::ColorType::Grayscale,
+            png::BitDepth::Sixteen,
+            |_| (),
+            &[0xFF, 0xFF, 0, 0, 0, 0, 0xFF, 0xFF],
+        );
+        assert_eq!(load(png), &[white, black, black, white]);
+
+        // grayscale with alpha
+        let png = encode_png_with(
+            None,
+            png::ColorType::GrayscaleAlpha,
+            png::BitDepth::Eight,
+            |_| (),
+            &[255, 255, 0, 0, 0, 255, 255, 0],
+        );
+        assert_eq!(load(png), &[white, None, black, None]);
+
+        // rgba
+        let png = encode_png_with(
+            None,
+            png::ColorType::Rgba,
+            png::BitDepth::Eight,
+            |_| (),
+            &[255, 255, 255, 255, 0, 0, 0, 0, 0, 0, 0, 255, 0, 0, 0, 0],
+        );
+        assert_eq!(load(png), &[white, None, black, None]);
+    }
+
+    #[test]
+    fn test_indexed() {
+        let palette = Palette::default();
+
+        // a palette that is the same as the game's is used as-is
+        let mut plte = Vec::new();
+        for i in 0..PALETTE_COLORS {
+            plte.extend_from_slice(&palette[i].to_srgba().to_u8_array()[..3]);
+        }
+
+        let png = encode_png_with(
+            None,
+            png::ColorType::Indexed,
+            png::BitDepth::Eight,
+            |encoder| {
+                encoder.set_palette(plte);
+                encoder.set_trns(vec![255, 255, 255, 0]);
+            },
+            &[1, 2, 3, 200],
+        );
+        let patch = load_truecolor(Cursor::new(png), &palette).unwrap();
+        assert_eq!(patch.data, &[Some(1), Some(2), None, Some(200)]);
+
+        // other palettes get remapped, even if they are packed
+        let png = encode_png_with(
+            None,
+            png::ColorType::Indexed,
+            png::BitDepth::One,
+            |encoder| {
+                encoder.set_palette(vec![0, 0, 0, 255, 255, 255]);
+            },
+            &[0b0100_0000, 0b1000_0000],
+        );
+        let white = Some(palette.nearest_color(Color::WHITE) as u8);
+        let black = Some(palette.nearest_color(Color::BLACK) as u8);
+        let patch = load_truecolor(Cursor::new(png), &palette).unwrap();
+        assert_eq!(patch.data, &[black, white, white, black]);
+    }
+}
@@ -0,0 +1,122 @@
+//! Unpacked folder loaders for spraycans.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ahash::HashMap;
+
+use eyre::{Report, WrapErr};
+
+use crate::doom::spray::Spray as DoomSpray;
+use crate::lump;
+use crate::spray::Spray;
+
+use super::scan::{scan_lua, scan_soc};
+
+/// A folder spray loader.
+///
+/// Like [`Pk3SprayLoader`](super::Pk3SprayLoader), this reads SOC from the
+/// `soc` folder and Lua from the `lua` folder.
+#[derive(Clone, Debug)]
+pub struct DirSprayLoader {
+    root: PathBuf,
+    files: Vec<PathBuf>,
+    file_index: usize,
+    sprays: HashMap<String, DoomSpray>,
+}
+
+impl DirSprayLoader {
+    /// Creates a new spray loader.
+    pub fn new(root: impl Into<PathBuf>) -> Result<DirSprayLoader, Report> {
+        let root = root.into();
+        let files = lump::files(&root)
+            .wrap_err_with(|| format!("failed reading folder \"{}\"", root.display()))?;
+
+        Ok(DirSprayLoader {
+            root,
+            files,
+            file_index: 0,
+            sprays: HashMap::default(),
+        })
+    }
+
+    fn read_lua(&mut self, ix: usize) -> Result<(), Report> {
+        let path = &self.files[ix];
+        let wrap_err = || format!("failed reading Lua \"{}\"", path.display());
+
+        let text = fs::read_to_string(self.root.join(path)).wrap_err_with(wrap_err)?;
+        scan_lua(&mut self.sprays, &text).wrap_err_with(wrap_err)
+    }
+
+    fn read_soc(&mut self, ix: usize) -> Result<(), Report> {
+        let path = &self.files[ix];
+        let wrap_err = || format!("failed reading SOC \"{}\"", path.display());
+
+        let text = fs::read_to_string(self.root.join(path)).wrap_err_with(wrap_err)?;
+        scan_soc(&mut self.sprays, &text).wrap_err_with(wrap_err)
+    }
+}
+
+impl Iterator for DirSprayLoader {
+    type Item = Result<Spray, Report>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.file_index < self.files.len() {
+            let file_index = self.file_index;
+            self.file_index += 1;
+
+            // check tld
+            let tld = self.files[file_index]
+                .components()
+                .next()
+                .and_then(|s| s.as_os_str().to_str());
+
+            let result = match tld {
+                Some(tld) if tld.eq_ignore_ascii_case("lua") => self.read_lua(file_index),
+                Some(tld) if tld.eq_ignore_ascii_case("soc") => self.read_soc(file_index),
+                // other file, ignore
+                _ => continue,
+            };
+
+            if let Err(err) = result {
+                return Some(Err(err));
+            }
+        }
+
+        if let Some(key) = self.sprays.keys().next().cloned() {
+            let spray = self.sprays.remove(&key).expect("present value");
+            Some(Ok(spray.into()))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{write, COLORS, COLORS_LUA, COLORS_SOC};
+
+    #[test]
+    fn test_dir_sprays() {
+        let dir = tempfile::tempdir().unwrap();
+
+        write(dir.path(), "soc/colors/maize.soc", COLORS_SOC);
+        write(dir.path(), "lua/colors.lua", COLORS_LUA);
+        write(
+            dir.path(),
+            "skins/sakura/S_SKIN",
+            "name = SKINCOLOR_NOTASPRAY\n",
+        );
+
+        let mut sprays = DirSprayLoader::new(dir.path())
+            .unwrap()
+            .map(|spray| spray.map(|spray| (spray.id.clone(), spray.name.clone())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        sprays.sort();
+
+        assert_eq!(sprays, COLORS.map(|(id, name)| (id.into(), name.into())));
+    }
+}
@@ -1,3 +1,8 @@
+pub mod dir;
 pub mod pk3;
+mod scan;
+pub mod wad;
 
+pub use dir::DirSprayLoader;
 pub use pk3::Pk3SprayLoader;
+pub use wad::WadSprayLoader;
@@ -0,0 +1,138 @@
+//! Skincolor scanning shared by the spray loaders.
+//!
+//! The loaders only differ in how they find SOC and Lua; the text itself is
+//! read the same way.
+
+use ahash::HashMap;
+
+use serde::Deserialize;
+
+use eyre::Report;
+
+use crate::doom::{
+    lua::{scan_whitespace, LiteralDeserializer},
+    soc::{Event, Parser},
+    spray::Spray as DoomSpray,
+};
+
+/// Scans Lua for skincolor declarations.
+pub fn scan_lua(sprays: &mut HashMap<String, DoomSpray>, text: &str) -> Result<(), Report> {
+    let mut text = text;
+
+    // find skincolor references
+    while let Some(ix) = text.find("skincolors") {
+        // skip skincolor name
+        text = &text[ix + 10..];
+
+        // skip to open bracket
+        let ix = scan_whitespace(text);
+        text = &text[ix..];
+
+        let name = if !text.is_empty() && text.as_bytes()[0] == b'[' {
+            // read skincolor name
+            if let Some(end_ix) = text.find(']') {
+                let name = &text[ix + 1..end_ix];
+                text = &text[end_ix + 1..];
+                name
+            } else {
+                // skip unclosed bracket
+                continue;
+            }
+        } else {
+            // skip random name
+            continue;
+        };
+
+        // skip to equals sign
+        let ix = scan_whitespace(text);
+        text = &text[ix..];
+
+        if !text.is_empty() && text.as_bytes()[0] == b'=' {
+            let spray = sprays
+                .entry(name.to_owned())
+                .or_insert_with_key(|key| DoomSpray {
+                    id: key.clone(),
+                    ..Default::default()
+                });
+
+            // skip to the actual declaration
+            text = &text[ix + 1..];
+            let ix = scan_whitespace(text);
+            text = &text[ix..];
+
+            let deser = LiteralDeserializer::new(text);
+
+            let deser_spray = OptionalSpray::deserialize(deser)?;
+
+            if let Some(name) = deser_spray.name {
+                spray.name = name;
+            }
+            if let Some(ramp) = deser_spray.ramp {
+                spray.ramp = ramp;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Scans SOC for skincolor freeslots and declarations.
+pub fn scan_soc(sprays: &mut HashMap<String, DoomSpray>, text: &str) -> Result<(), Report> {
+    // open soc with parser
+    let mut parser = Parser::new(text);
+
+    while let Some(ev) = parser.next() {
+        match ev {
+            Event::Freeslot(name) if is_skincolor_name(name) => {
+                sprays
+                    .entry(name.to_owned())
+                    .or_insert_with_key(|key| DoomSpray {
+                        id: key.clone(),
+                        ..Default::default()
+                    });
+            }
+            Event::Header {
+                name,
+                value: Some(value),
+            } if name.eq_ignore_ascii_case("SKINCOLOR") && is_skincolor_name(value) => {
+                let spray = sprays
+                    .entry(value.to_owned())
+                    .or_insert_with_key(|key| DoomSpray {
+                        id: key.clone(),
+                        ..Default::default()
+                    });
+
+                let deser_spray = parser.deserialize::<OptionalSpray>()?;
+
+                if let Some(name) = deser_spray.name {
+                    spray.name = name;
+                }
+                if let Some(ramp) = deser_spray.ramp {
+                    spray.ramp = ramp;
+                }
+            }
+            // skip unknonwn directives
+            _ => (),
+        }
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct OptionalSpray {
+    #[serde(default)]
+    pub name: Option<String>,
+    #[serde(default)]
+    pub ramp: Option<[u8; 16]>,
+}
+
+fn is_skincolor_name(name: &str) -> bool {
+    const PREFIX: &str = "SKINCOLOR_";
+
+    if name.len() >= PREFIX.len() {
+        name[..PREFIX.len()].eq_ignore_ascii_case(PREFIX)
+    } else {
+        false
+    }
+}
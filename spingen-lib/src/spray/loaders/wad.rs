@@ -0,0 +1,154 @@
+//! WAD loaders for spraycans.
+//!
+//! WADs carry SOC in `SOC_*`, `MAINCFG` and `OBJCTCFG` lumps, and Lua in
+//! `LUA_*` lumps.
+
+use std::io::{Cursor, Read};
+
+use bytes::Bytes;
+
+use ahash::HashMap;
+
+use wad::{Archive, Name};
+
+use eyre::{Report, WrapErr};
+
+use derive_more::Display;
+
+use crate::doom::spray::Spray as DoomSpray;
+use crate::spray::Spray;
+
+use super::scan::{scan_lua, scan_soc};
+
+/// A WAD spray loader.
+#[derive(Clone, Debug)]
+pub struct WadSprayLoader {
+    wad: Archive<Cursor<Bytes>>,
+    lump_index: usize,
+    sprays: HashMap<String, DoomSpray>,
+}
+
+impl WadSprayLoader {
+    /// Creates a new spray loader.
+    pub fn new(bytes: impl Into<Bytes>) -> Result<WadSprayLoader, Report> {
+        let bytes = bytes.into();
+        Archive::new(Cursor::new(bytes))
+            .map(|wad| WadSprayLoader {
+                wad,
+                lump_index: 0,
+                sprays: HashMap::default(),
+            })
+            .map_err(From::from)
+    }
+
+    fn read_lump(&mut self, ix: usize, kind: LumpKind) -> Result<(), Report> {
+        let mut entry = self.wad.get(ix).expect("valid entry");
+        let name = *entry.name();
+
+        let mut text = String::with_capacity(entry.bytes_len());
+        entry
+            .read_to_string(&mut text)
+            .wrap_err_with(|| format!("failed reading {} \"{}\"", kind, name))?;
+
+        match kind {
+            LumpKind::Lua => scan_lua(&mut self.sprays, &text),
+            LumpKind::Soc => scan_soc(&mut self.sprays, &text),
+        }
+        .wrap_err_with(|| format!("failed reading {} \"{}\"", kind, name))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Display)]
+enum LumpKind {
+    #[display("Lua")]
+    Lua,
+    #[display("SOC")]
+    Soc,
+}
+
+impl LumpKind {
+    fn of(name: &Name) -> Option<LumpKind> {
+        let name = name.as_str();
+        let has_prefix = |prefix: &str| {
+            name.len() >= prefix.len() && name[..prefix.len()].eq_ignore_ascii_case(prefix)
+        };
+
+        if has_prefix("LUA_") {
+            Some(LumpKind::Lua)
+        } else if has_prefix("SOC_")
+            || name.eq_ignore_ascii_case("MAINCFG")
+            || name.eq_ignore_ascii_case("OBJCTCFG")
+        {
+            Some(LumpKind::Soc)
+        } else {
+            None
+        }
+    }
+}
+
+impl Iterator for WadSprayLoader {
+    type Item = Result<Spray, Report>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.lump_index < self.wad.len() {
+            let lump_index = self.lump_index;
+            self.lump_index += 1;
+
+            // find either an soc or a lua lump
+            let kind = {
+                let entry = self.wad.get(lump_index).expect("valid entry");
+                LumpKind::of(entry.name())
+            };
+            let Some(kind) = kind else {
+                continue;
+            };
+
+            if let Err(err) = self.read_lump(lump_index, kind) {
+                return Some(Err(err));
+            }
+        }
+
+        if let Some(key) = self.sprays.keys().next().cloned() {
+            let spray = self.sprays.remove(&key).expect("present value");
+            Some(Ok(spray.into()))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::testing::{pwad, COLORS, COLORS_LUA, COLORS_SOC};
+
+    #[test]
+    fn test_wad_sprays() {
+        let wad = pwad(&[
+            ("SOC_COLR", COLORS_SOC.as_bytes()),
+            ("LUA_COLR", COLORS_LUA.as_bytes()),
+            ("S_SKIN", b"name = SKINCOLOR_NOTASPRAY\n"),
+        ]);
+
+        let mut sprays = WadSprayLoader::new(wad)
+            .unwrap()
+            .map(|spray| spray.map(|spray| (spray.id.clone(), spray.name.clone())))
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        sprays.sort();
+
+        assert_eq!(sprays, COLORS.map(|(id, name)| (id.into(), name.into())));
+    }
+
+    #[test]
+    fn test_lump_kind() {
+        let kind = |name: &str| LumpKind::of(&name.parse().unwrap());
+
+        assert!(matches!(kind("LUA_COLR"), Some(LumpKind::Lua)));
+        assert!(matches!(kind("SOC_SAKR"), Some(LumpKind::Soc)));
+        assert!(matches!(kind("MAINCFG"), Some(LumpKind::Soc)));
+        assert!(matches!(kind("OBJCTCFG"), Some(LumpKind::Soc)));
+        assert!(kind("S_SKIN").is_none());
+    }
+}
@@ -7,26 +7,10 @@ use crate::doom::spray::{sprays as doom_sprays, Spray as DoomSpray};
 use std::ops::Deref;
 use std::sync::Arc;
 
-use wasm_bindgen::prelude::*;
-
 /// An easily cloneable spray can.
 #[derive(Clone, Debug, Default, PartialEq)]
-#[wasm_bindgen]
 pub struct Spray(Arc<DoomSpray>);
 
-#[wasm_bindgen]
-impl Spray {
-    #[wasm_bindgen(getter)]
-    pub fn id(&self) -> String {
-        self.id.clone()
-    }
-
-    #[wasm_bindgen(getter)]
-    pub fn name(&self) -> String {
-        self.name.clone()
-    }
-}
-
 impl From<DoomSpray> for Spray {
     fn from(value: DoomSpray) -> Self {
         Spray(Arc::new(value))
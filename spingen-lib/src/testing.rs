@@ -0,0 +1,44 @@
+//! Shared test fixtures.
+
+use std::fs;
+use std::path::Path;
+
+/// SOC that freeslots and defines `SKINCOLOR_MAIZE`.
+pub const COLORS_SOC: &str =
+    "Freeslot\nSKINCOLOR_MAIZE\n\nSkincolor SKINCOLOR_MAIZE\nName = Maize\n";
+
+/// Lua that defines `SKINCOLOR_TEA`.
+pub const COLORS_LUA: &str = "skincolors[SKINCOLOR_TEA] = {name = \"Tea\"}\n";
+
+/// The ids and names of the sprays in [`COLORS_SOC`] and [`COLORS_LUA`].
+pub const COLORS: [(&str, &str); 2] = [("SKINCOLOR_MAIZE", "Maize"), ("SKINCOLOR_TEA", "Tea")];
+
+/// Builds a PWAD out of its lumps.
+pub fn pwad(lumps: &[(&str, &[u8])]) -> Vec<u8> {
+    let mut data = Vec::<u8>::new();
+    let mut dir = Vec::<u8>::new();
+
+    for (name, text) in lumps {
+        let mut entry_name = [0u8; 8];
+        entry_name[..name.len()].copy_from_slice(name.as_bytes());
+
+        dir.extend(((12 + data.len()) as i32).to_le_bytes());
+        dir.extend((text.len() as i32).to_le_bytes());
+        dir.extend(entry_name);
+        data.extend(*text);
+    }
+
+    let mut wad = b"PWAD".to_vec();
+    wad.extend((lumps.len() as i32).to_le_bytes());
+    wad.extend(((12 + data.len()) as i32).to_le_bytes());
+    wad.extend(data);
+    wad.extend(dir);
+    wad
+}
+
+/// Writes a file into a folder, creating its parent folders.
+pub fn write(dir: &Path, path: &str, contents: impl AsRef<[u8]>) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, contents).unwrap();
+}
@@ -0,0 +1,724 @@
+//! The JS bindings to `spingen`.
+//!
+//! This is a thin layer over the rest of the crate; anything that isn't
+//! specific to JS belongs in the core modules instead.
+
+use gloo::file::{futures::read_as_bytes, File};
+
+use crate::image::{spray_to_image, AnimationFormat, Encoder, GifOptions};
+use crate::load_order::{AddonFile, LoadOrder};
+use crate::skin::{
+    catalogue::{Spr2Category, Spr2Info},
+    portrait::PortraitKind,
+    FrameNumber, Skin, SpriteAngle,
+};
+use crate::spray::{sprays, Spray};
+
+use std::io::Cursor;
+
+use wad::Name;
+
+use web_sys::Url;
+
+use bytes::Bytes;
+
+use wasm_bindgen::prelude::*;
+
+use log::Level;
+
+use eyre::WrapErr;
+
+#[wasm_bindgen(start)]
+fn start() {
+    console_error_panic_hook::set_once();
+    console_log::init_with_level(Level::Debug).unwrap();
+}
+
+/// Spingen entry point.
+#[wasm_bindgen]
+#[derive(Debug, Clone, Default)]
+pub struct Spingen {
+    load_order: LoadOrder,
+}
+
+#[wasm_bindgen]
+impl Spingen {
+    /// Creates a new `Spingen`.
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Spingen {
+        Spingen::default()
+    }
+
+    /// Loads the default sprays.
+    #[wasm_bindgen(js_name = fetchDefaultSprays)]
+    pub fn fetch_default_sprays(&mut self) -> Vec<JsSpray> {
+        sprays().into_iter().map(JsSpray::from).collect()
+    }
+
+    /// Loads sprays and skins from a file.
+    ///
+    /// The format is detected from the contents, not the file name. Fails if
+    /// the file is neither a pk3 nor a WAD.
+    #[wasm_bindgen(js_name = fetchAll)]
+    pub async fn fetch_all(
+        &mut self,
+        blob: &web_sys::File,
+        resolve_spray: &js_sys::Function,
+        resolve_skin: &js_sys::Function,
+    ) -> Result<(), JsValue> {
+        let file = File::from(blob.clone());
+        let name = file.name();
+
+        let file = read_as_bytes(&file)
+            .await
+            .map(Bytes::from)
+            .map_err(|err| JsValue::from(format!("failed reading \"{}\": {}", name, err)))?;
+
+        let addon = AddonFile::read(name.clone(), file)
+            .map_err(|err| JsValue::from(format!("failed loading \"{}\": {:?}", name, err)))?;
+        let addon = self.load_order.push(addon);
+
+        for spray in addon.sprays.iter() {
+            let _ = resolve_spray.call1(&JsValue::null(), &JsSpray::from(spray.clone()).into());
+        }
+
+        for skin in addon.skins.iter() {
+            let _ = resolve_skin.call1(&JsValue::null(), &JsSkin::from(skin.clone()).into());
+        }
+
+        Ok(())
+    }
+
+    /// The names of the loaded files, in load order.
+    #[wasm_bindgen(js_name = loadOrder)]
+    pub fn load_order(&self) -> Vec<String> {
+        self.load_order
+            .files()
+            .iter()
+            .map(|file| file.name.clone())
+            .collect()
+    }
+
+    /// Unloads a file.
+    ///
+    /// Returns `false` if the file was never loaded.
+    #[wasm_bindgen(js_name = removeFile)]
+    pub fn remove_file(&mut self, name: String) -> bool {
+        self.load_order.remove(&name).is_some()
+    }
+
+    /// Reports what got overridden by later files, and what references between
+    /// files stayed unresolved.
+    #[wasm_bindgen(js_name = loadReport)]
+    pub fn load_report(&self) -> JsLoadReport {
+        let report = self.load_order.report();
+
+        JsLoadReport {
+            overridden: report
+                .overridden
+                .into_iter()
+                .map(|overridden| JsOverride {
+                    kind: overridden.kind.to_string(),
+                    id: overridden.id,
+                    file: overridden.file,
+                    by: overridden.by,
+                })
+                .collect(),
+            unresolved: report
+                .unresolved
+                .into_iter()
+                .map(|unresolved| JsUnresolved {
+                    file: unresolved.file,
+                    skin: unresolved.skin,
+                    field: unresolved.field.to_owned(),
+                    value: unresolved.value,
+                })
+                .collect(),
+        }
+    }
+
+    /// Generates a spraycan image.
+    #[wasm_bindgen(js_name = generateSprayImage)]
+    pub fn generate_spray_image(&self, spray_id: String) -> Result<String, JsValue> {
+        // get spray
+        let Some(spray) = self.load_order.spray(&spray_id) else {
+            return Err(format!("spray \"{}\" not found", spray_id).into());
+        };
+
+        // write to png
+        let mut buf = Vec::new();
+        spray_to_image(Cursor::new(&mut buf), spray)
+            .wrap_err("failed to generate spraycan graphic")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+
+        let name = format!("{}.png", spray.name);
+        let file = File::new_with_options(&name, &buf[..], Some("image/png"), None);
+
+        Url::create_object_url_with_blob(file.as_ref())
+    }
+
+    /// Generates a skin animation.
+    #[wasm_bindgen(js_name = generateSkinAnimation)]
+    pub fn generate_skin_animation(
+        &self,
+        skin_id: String,
+        spray_id: Option<String>,
+        sprite: String,
+        frame: String,
+        options: JsGifOptions,
+    ) -> Result<JsSkinImage, JsValue> {
+        // try to parse input
+        let name = Name::from_bytes(sprite.as_bytes())
+            .wrap_err("invalid `sprite` parameter")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+        let Some(frame) = (match frame.as_bytes() {
+            [frame] => FrameNumber::from_ascii_char(*frame),
+            _ => None,
+        }) else {
+            return Err(format!("invalid `frame` parameter: \"{}\"", frame).into());
+        };
+
+        let (skin, spray) = self.get_skin_and_spray(skin_id, spray_id)?;
+        let mut encoder = Encoder::new(&skin).with_spray(&spray);
+
+        // generate new gif
+        let mut buf = Vec::new();
+        let encoded_kind = encoder
+            .sprite_gif_with_options(Cursor::new(&mut buf), name, frame, options.into())
+            .wrap_err("failed to encode gif")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+
+        let resolved = skin.resolve(&name).expect("encoded sprite exists");
+        let name = format!("{}.{}", resolved.name, encoded_kind.as_extension());
+
+        let file = File::new_with_options(&name, &buf[..], Some(encoded_kind.as_mime_type()), None);
+
+        Ok(JsSkinImage {
+            url: Url::create_object_url_with_blob(file.as_ref())?,
+            sprite: resolved.name.to_string(),
+            fallback: resolved.is_fallback(),
+        })
+    }
+
+    /// Generates a skin animation that plays every frame of a sprite at one
+    /// angle.
+    #[wasm_bindgen(js_name = generateSkinFrameAnimation)]
+    pub fn generate_skin_frame_animation(
+        &self,
+        skin_id: String,
+        spray_id: Option<String>,
+        sprite: String,
+        angle: String,
+        options: JsGifOptions,
+    ) -> Result<JsSkinImage, JsValue> {
+        // try to parse input
+        let name = Name::from_bytes(sprite.as_bytes())
+            .wrap_err("invalid `sprite` parameter")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+        let Some(angle) = (match angle.as_bytes() {
+            [angle] => SpriteAngle::from_ascii_char(*angle),
+            _ => None,
+        }) else {
+            return Err(format!("invalid `angle` parameter: \"{}\"", angle).into());
+        };
+
+        let (skin, spray) = self.get_skin_and_spray(skin_id, spray_id)?;
+        let mut encoder = Encoder::new(skin).with_spray(spray);
+
+        // generate new gif
+        let mut buf = Vec::new();
+        let encoded_kind = encoder
+            .sprite_frames_with_options(Cursor::new(&mut buf), name, angle, options.into())
+            .wrap_err("failed to encode gif")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+
+        let resolved = skin.resolve(&name).expect("encoded sprite exists");
+        let name = format!("{}.{}", resolved.name, encoded_kind.as_extension());
+
+        let file = File::new_with_options(&name, &buf[..], Some(encoded_kind.as_mime_type()), None);
+
+        Ok(JsSkinImage {
+            url: Url::create_object_url_with_blob(file.as_ref())?,
+            sprite: resolved.name.to_string(),
+            fallback: resolved.is_fallback(),
+        })
+    }
+
+    /// Generates a sprite sheet of a sprite, or of the whole skin if no
+    /// sprite is given.
+    #[wasm_bindgen(js_name = generateSpriteSheet)]
+    pub fn generate_sprite_sheet(
+        &self,
+        skin_id: String,
+        spray_id: Option<String>,
+        sprite: Option<String>,
+        options: JsGifOptions,
+    ) -> Result<JsSpriteSheet, JsValue> {
+        let (skin, spray) = self.get_skin_and_spray(skin_id, spray_id)?;
+        let mut encoder = Encoder::new(skin).with_spray(spray);
+
+        let mut buf = Vec::new();
+        let (name, sheet) = match sprite {
+            Some(sprite) => {
+                let name = Name::from_bytes(sprite.as_bytes())
+                    .wrap_err("invalid `sprite` parameter")
+                    .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+                let sheet = encoder.sprite_sheet(Cursor::new(&mut buf), name, options.into());
+                (format!("{}_{}.png", skin.name, name), sheet)
+            }
+            None => {
+                let sheet = encoder.skin_sheet(Cursor::new(&mut buf), options.into());
+                (format!("{}.png", skin.name), sheet)
+            }
+        };
+        let sheet = sheet
+            .wrap_err("failed to encode sprite sheet")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+
+        let file = File::new_with_options(&name, &buf[..], Some("image/png"), None);
+
+        Ok(JsSpriteSheet {
+            image: Url::create_object_url_with_blob(file.as_ref())?,
+            json: serde_json::to_string(&sheet).expect("valid json"),
+        })
+    }
+
+    /// Generates a skin thumbnail.
+    #[wasm_bindgen(js_name = generateSkinThumbnail)]
+    pub fn generate_skin_thumbnail(
+        &self,
+        skin_id: String,
+        spray_id: Option<String>,
+    ) -> Result<JsSkinImage, JsValue> {
+        let (skin, spray) = self.get_skin_and_spray(skin_id, spray_id)?;
+        let mut encoder = Encoder::new(skin).with_spray(spray);
+
+        let name = "STIN".parse::<Name>().expect("valid name");
+
+        let mut buf = Vec::new();
+        encoder
+            .sprite_still(
+                Cursor::new(&mut buf),
+                name,
+                FrameNumber::A,
+                SpriteAngle::RIGHT_FORWARD,
+            )
+            .wrap_err("failed to encode thumbnail")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+
+        let resolved = skin.resolve(&name).expect("encoded sprite exists");
+        let name = format!("{}.png", skin.realname.replace('_', " "));
+        let file = File::new_with_options(&name, &buf[..], Some("image/png"), None);
+
+        Ok(JsSkinImage {
+            url: Url::create_object_url_with_blob(file.as_ref())?,
+            sprite: resolved.name.to_string(),
+            fallback: resolved.is_fallback(),
+        })
+    }
+
+    /// Generates one of a skin's portraits, `rank`, `wanted` or `minimap`.
+    #[wasm_bindgen(js_name = generateSkinPortrait)]
+    pub fn generate_skin_portrait(
+        &self,
+        skin_id: String,
+        spray_id: Option<String>,
+        kind: String,
+        options: JsGifOptions,
+    ) -> Result<String, JsValue> {
+        let kind = kind
+            .parse::<PortraitKind>()
+            .map_err(|err| JsValue::from(format!("{}", err)))?;
+
+        let (skin, spray) = self.get_skin_and_spray(skin_id, spray_id)?;
+        let mut encoder = Encoder::new(skin).with_spray(spray);
+
+        let mut buf = Vec::new();
+        encoder
+            .portrait(Cursor::new(&mut buf), kind, options.into())
+            .wrap_err("failed to encode portrait")
+            .map_err(|err| JsValue::from(format!("{:?}", err)))?;
+
+        let name = format!("{}.png", kind.lump_name(skin));
+        let file = File::new_with_options(&name, &buf[..], Some("image/png"), None);
+
+        Url::create_object_url_with_blob(file.as_ref())
+    }
+
+    fn get_skin_and_spray(
+        &self,
+        skin_id: String,
+        spray_id: Option<String>,
+    ) -> Result<(&Skin, &Spray), JsValue> {
+        // get skin
+        let Some(skin) = self.load_order.skin(&skin_id) else {
+            return Err(format!("skin \"{}\" not found", skin_id).into());
+        };
+
+        // get spray if it exists
+        let spray = if let Some(spray_id) = spray_id {
+            match self.load_order.spray(&spray_id) {
+                Some(spray) => spray,
+                None => return Err(format!("spray \"{}\" not found", spray_id).into()),
+            }
+        } else {
+            if let Some(spray) = self.load_order.prefcolor(skin) {
+                spray
+            } else {
+                warn!("invalid prefcolor {:?}, using default", skin.prefcolor);
+                self.load_order.sprays()[0]
+            }
+        };
+
+        Ok((skin, spray))
+    }
+}
+
+/// A skin, exposed to JS.
+#[wasm_bindgen(js_name = Skin)]
+#[derive(Clone, Debug)]
+pub struct JsSkin(Skin);
+
+#[wasm_bindgen(js_class = Skin)]
+impl JsSkin {
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.0.name.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn realname(&self) -> String {
+        self.0.realname.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kartspeed(&self) -> i32 {
+        self.0.kartspeed
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn kartweight(&self) -> i32 {
+        self.0.kartweight
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn startcolor(&self) -> u8 {
+        self.0.startcolor
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn prefcolor(&self) -> String {
+        self.0.prefcolor.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn prefoffcolor(&self) -> Option<String> {
+        self.0.prefoffcolor.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn machine(&self) -> bool {
+        self.0.flags.machine
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn ironman(&self) -> bool {
+        self.0.flags.ironman
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn badnik(&self) -> bool {
+        self.0.flags.badnik
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn rivals(&self) -> Vec<String> {
+        self.0.rivals.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn facerank(&self) -> String {
+        self.0.facerank.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn facewant(&self) -> String {
+        self.0.facewant.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn facemmap(&self) -> String {
+        self.0.facemmap.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn highresscale(&self) -> f32 {
+        self.0.highresscale
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn followers(&self) -> Vec<String> {
+        self.0.followers.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn sounds(&self) -> Vec<JsSkinSound> {
+        self.0
+            .sounds
+            .iter()
+            .map(|sound| JsSkinSound {
+                sound: sound.sound.clone(),
+                lump: sound.lump.clone(),
+            })
+            .collect()
+    }
+
+    pub fn sprites(&self) -> Vec<String> {
+        self.0
+            .sprites()
+            .into_iter()
+            .map(|name| name.as_str().to_owned())
+            .collect()
+    }
+
+    /// Describes a sprite using the built-in catalogue.
+    #[wasm_bindgen(js_name = spriteInfo)]
+    pub fn sprite_info(&self, sprite: String) -> Result<JsSpriteInfo, JsValue> {
+        let name = sprite
+            .parse::<Name>()
+            .map_err(|err| JsValue::from(format!("{}", err)))?;
+
+        Ok(match Spr2Info::get(&name) {
+            Some(info) => JsSpriteInfo {
+                name: info.name.to_owned(),
+                category: info.category.to_string(),
+                animates: info.animates,
+            },
+            None => JsSpriteInfo {
+                name: name.as_str().to_owned(),
+                category: Spr2Category::Other.to_string(),
+                animates: self.0.iter_frames(&name).nth(1).is_some(),
+            },
+        })
+    }
+
+    pub fn frames(&self, frame: String) -> Result<Vec<String>, JsValue> {
+        let name = frame
+            .parse::<Name>()
+            .map_err(|err| JsValue::from(format!("{}", err)))?;
+
+        let mut frames = self.0.iter_frames(&name).collect::<Vec<_>>();
+        frames.sort();
+        Ok(frames.into_iter().map(|frame| frame.to_string()).collect())
+    }
+}
+
+impl From<Skin> for JsSkin {
+    fn from(value: Skin) -> Self {
+        JsSkin(value)
+    }
+}
+
+/// A load report, exposed to JS.
+///
+/// See [`LoadReport`](crate::load_order::LoadReport).
+#[wasm_bindgen(js_name = LoadReport, getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct JsLoadReport {
+    /// Skins and sprays replaced by a later file, in load order.
+    pub overridden: Vec<JsOverride>,
+    /// References to skins or sprays that no file provides.
+    pub unresolved: Vec<JsUnresolved>,
+}
+
+/// An overridden skin or spray, exposed to JS.
+#[wasm_bindgen(js_name = Override, getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct JsOverride {
+    /// Either `skin` or `spray`.
+    pub kind: String,
+    /// The skin name or spray id.
+    pub id: String,
+    /// The file it came from, or `undefined` for the base game.
+    pub file: Option<String>,
+    /// The file that replaced it.
+    pub by: String,
+}
+
+/// An unresolved reference, exposed to JS.
+#[wasm_bindgen(js_name = Unresolved, getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct JsUnresolved {
+    /// The file of the skin.
+    pub file: String,
+    /// The name of the skin.
+    pub skin: String,
+    /// The `S_SKIN` field holding the reference.
+    pub field: String,
+    /// The name that could not be found.
+    pub value: String,
+}
+
+/// A sound replaced by a skin, exposed to JS.
+///
+/// See [`SkinSound`](crate::doom::skin::SkinSound).
+#[wasm_bindgen(js_name = SkinSound, getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct JsSkinSound {
+    /// The sound being replaced.
+    pub sound: String,
+    /// The name of the sound lump to play instead.
+    pub lump: String,
+}
+
+/// A sprite's catalogue entry, exposed to JS.
+///
+/// See [`Spr2Info`].
+#[wasm_bindgen(js_name = SpriteInfo, getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct JsSpriteInfo {
+    /// The human-readable name.
+    pub name: String,
+    /// The group the sprite belongs to.
+    pub category: String,
+    /// Whether the game cycles through the frames of the sprite.
+    pub animates: bool,
+}
+
+/// A generated skin image, exposed to JS.
+#[wasm_bindgen(js_name = SkinImage, getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct JsSkinImage {
+    /// The object URL of the image.
+    pub url: String,
+    /// The sprite that was drawn.
+    pub sprite: String,
+    /// Whether the skin lacked the requested sprite, and `sprite` is what
+    /// the game would fall back to.
+    pub fallback: bool,
+}
+
+/// A generated sprite sheet, exposed to JS.
+#[wasm_bindgen(js_name = SpriteSheet, getter_with_clone)]
+#[derive(Clone, Debug)]
+pub struct JsSpriteSheet {
+    /// The object URL of the sheet PNG.
+    pub image: String,
+    /// The [`SpriteSheet`](crate::image::sheet::SpriteSheet) descriptor, as
+    /// JSON.
+    pub json: String,
+}
+
+/// A spray, exposed to JS.
+#[wasm_bindgen(js_name = Spray)]
+#[derive(Clone, Debug)]
+pub struct JsSpray(Spray);
+
+#[wasm_bindgen(js_class = Spray)]
+impl JsSpray {
+    #[wasm_bindgen(getter)]
+    pub fn id(&self) -> String {
+        self.0.id.clone()
+    }
+
+    #[wasm_bindgen(getter)]
+    pub fn name(&self) -> String {
+        self.0.name.clone()
+    }
+}
+
+impl From<Spray> for JsSpray {
+    fn from(value: Spray) -> Self {
+        JsSpray(value)
+    }
+}
+
+/// GIF encode options, exposed to JS.
+///
+/// See [`GifOptions`].
+#[wasm_bindgen(js_name = GifOptions)]
+#[derive(Clone, Copy, Debug)]
+pub struct JsGifOptions {
+    /// The factor to upscale by.
+    pub scale: f32,
+    /// The delay between each frame, in centiseconds.
+    pub delay: u16,
+    /// Whether to mirror across the X axis.
+    pub mirror: bool,
+    /// The format to encode animations in.
+    pub format: JsAnimationFormat,
+    /// Whether to draw sprites at the resolution they were drawn at,
+    /// ignoring the skin's `highresscale`.
+    pub native: bool,
+}
+
+#[wasm_bindgen(js_class = GifOptions)]
+impl JsGifOptions {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> JsGifOptions {
+        JsGifOptions::default()
+    }
+}
+
+impl Default for JsGifOptions {
+    fn default() -> Self {
+        GifOptions::default().into()
+    }
+}
+
+impl From<GifOptions> for JsGifOptions {
+    fn from(value: GifOptions) -> Self {
+        JsGifOptions {
+            scale: value.scale,
+            delay: value.delay,
+            mirror: value.mirror,
+            format: value.format.into(),
+            native: value.native,
+        }
+    }
+}
+
+impl From<JsGifOptions> for GifOptions {
+    fn from(value: JsGifOptions) -> Self {
+        GifOptions {
+            scale: value.scale,
+            delay: value.delay,
+            mirror: value.mirror,
+            format: value.format.into(),
+            native: value.native,
+        }
+    }
+}
+
+/// The format of an animation, exposed to JS.
+///
+/// See [`AnimationFormat`].
+#[wasm_bindgen(js_name = AnimationFormat)]
+#[derive(Clone, Copy, Debug)]
+pub enum JsAnimationFormat {
+    Gif = "gif",
+    Apng = "apng",
+    Webp = "webp",
+}
+
+impl From<AnimationFormat> for JsAnimationFormat {
+    fn from(value: AnimationFormat) -> Self {
+        match value {
+            AnimationFormat::Gif => JsAnimationFormat::Gif,
+            AnimationFormat::Apng => JsAnimationFormat::Apng,
+            AnimationFormat::Webp => JsAnimationFormat::Webp,
+        }
+    }
+}
+
+impl From<JsAnimationFormat> for AnimationFormat {
+    fn from(value: JsAnimationFormat) -> Self {
+        match value {
+            JsAnimationFormat::Gif => AnimationFormat::Gif,
+            JsAnimationFormat::Apng => AnimationFormat::Apng,
+            JsAnimationFormat::Webp => AnimationFormat::Webp,
+            // string enums can be given any string from JS
+            JsAnimationFormat::__Invalid => AnimationFormat::default(),
+        }
+    }
+}
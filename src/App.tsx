@@ -50,18 +50,24 @@ function App() {
   const spingen = new Spingen();
 
   // setup events
+  // later files override earlier skins and sprays with the same id, ignoring
+  // case like the game
   spingen.onSpray = (spray: Spray) => {
-    setSprays((sprays) => sprays.concat([spray]));
+    setSprays((sprays) => sprays
+      .filter((other) => other.id.toLowerCase() !== spray.id.toLowerCase())
+      .concat([spray]));
   };
   spingen.onSkin = (skin: Skin) => {
     setSkins((skins) => {
       const [spray, setSpray] = createSignal<Spray>();
 
-      return skins.concat([{
-        spray,
-        setSpray,
-        ...skin
-      }]);
+      return skins
+        .filter((other) => other.name.toLowerCase() !== skin.name.toLowerCase())
+        .concat([{
+          spray,
+          setSpray,
+          ...skin
+        }]);
     });
   };
   spingen.onReady = (otherSprays: Spray[]) => {
@@ -45,7 +45,6 @@ const Header = (props: HeaderProps) => {
       </p>
       <input
         type="file"
-        accept=".pk3,.wad"
         multiple={true}
         class="hidden"
         on:change={onInputFile}
@@ -1,6 +1,7 @@
-import { For } from 'solid-js';
+import { createMemo, For } from 'solid-js';
 
 import { SkinWithSpray } from './SkinSelect';
+import { Sprite } from '../spingen';
 
 export interface SpriteSelectProps {
   skin: () => SkinWithSpray | undefined;
@@ -8,82 +9,24 @@ export interface SpriteSelectProps {
   onChange: (sprite: string) => void;
 }
 
-function asHumanReadable(name: string): string {
-  switch (name) {
-    case "STIN":
-      return "Still";
-    case "STIL":
-      return "Still Left";
-    case "STIR":
-      return "Still Right";
-    case "STGL":
-      return "Still Left (glance back)";
-    case "STGR":
-      return "Still Right (glance back)";
-    case "STLL":
-      return "Still Left (look back)";
-    case "STLR":
-      return "Still Right (look back)";
-    case "SLWN":
-      return "Slow Driving";
-    case "SLWL":
-      return "Slow Driving Left";
-    case "SLWR":
-      return "Slow Driving Right";
-    case "SLGL":
-      return "Slow Driving Left (glance back)";
-    case "SLGR":
-      return "Slow Driving Right (glance back)";
-    case "SLLL":
-      return "Slow Driving Left (look back)";
-    case "SLLR":
-      return "Slow Driving Right (look back)";
-    case "FSTN":
-      return "Fast Driving";
-    case "FSTL":
-      return "Fast Driving Left";
-    case "FSTR":
-      return "Fast Driving Right";
-    case "FSGL":
-      return "Fast Driving Left (glance back)";
-    case "FSGR":
-      return "Fast Driving Right (glance back)";
-    case "FSLL":
-      return "Fast Driving Left (look back)";
-    case "FSLR":
-      return "Fast Driving Right (look back)";
-    case "DRLN":
-      return "Drifting Left, Steering Neutral";
-    case "DRLO":
-      return "Drifting Left, Steering Outwards";
-    case "DRLI":
-      return "Drifting Left, Steering Inwards";
-    case "DRRN":
-      return "Drifting Right, Steering Neutral";
-    case "DRRO":
-      return "Drifting Right, Steering Outwards";
-    case "DRRI":
-      return "Drifting Right, Steering Inwards";
-    case "SPIN":
-      return "Spinout";
-    case "DEAD":
-      return "Dead";
-    case "SIGN":
-      return "Finish Signpost";
-    case "SIGL":
-      return "Finish Signpost, Ironman Perfect";
-    case "SSIG":
-      return "\"working designs\" Signpost";
-    case "XTRA":
-      return "Wanted";
-    case "TALK":
-      return "Dialogue Icon";
-    default:
-      return name;
-  }
+function capitalize(category: string): string {
+  return category.replace(/\b\w/g, (ch) => ch.toUpperCase());
 }
 
 const SpriteSelect = (props: SpriteSelectProps) => {
+  // sprites come in catalogue order, so grouping keeps the order intact
+  const categories = createMemo(() => {
+    const categories = new Map<string, [string, Sprite][]>();
+
+    for (const [id, sprite] of props.skin()?.sprites ?? []) {
+      const sprites = categories.get(sprite.category) ?? [];
+      sprites.push([id, sprite]);
+      categories.set(sprite.category, sprites);
+    }
+
+    return categories;
+  });
+
   return (
     <select
       value={props.value()}
@@ -91,10 +34,18 @@ const SpriteSelect = (props: SpriteSelectProps) => {
         props.onChange(ev.target.value);
       }}
     >
-      <For each={Array.from(props.skin()?.sprites.keys() ?? [])}>
-        {(item, _index) => {
+      <For each={Array.from(categories().entries())}>
+        {([category, sprites], _index) => {
           return (
-            <option value={item}>{asHumanReadable(item)}</option>
+            <optgroup label={capitalize(category)}>
+              <For each={sprites}>
+                {([id, sprite], _index) => {
+                  return (
+                    <option value={id}>{sprite.name}</option>
+                  );
+                }}
+              </For>
+            </optgroup>
           );
         }}
       </For>
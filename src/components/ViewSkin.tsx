@@ -18,6 +18,18 @@ export enum SpriteScale {
   X8 = "8x",
 }
 
+export enum SpriteFormat {
+  Gif = "gif",
+  Apng = "apng",
+  Webp = "webp",
+}
+
+const ALLOWED_FORMATS: SpriteFormat[] = [
+  SpriteFormat.Gif,
+  SpriteFormat.Apng,
+  SpriteFormat.Webp,
+];
+
 function skinClass(skin: SkinWithSpray) {
   const CLASSES = [
     "Class A", "Class B", "Class C", "Class D", "Class E", "Class F", "Class G", "Class H",
@@ -62,6 +74,7 @@ const ViewSkin = (props: ViewSkinProps) => {
   const [sprite, setSprite] = createSignal<string>("STIN");
   const [frame, setFrame] = createSignal<string>("A");
   const [scale, setScale] = createSignal<SpriteScale>(SpriteScale.X1);
+  const [format, setFormat] = createSignal<SpriteFormat>(SpriteFormat.Gif);
 
   const memoizedSprite = createMemo(() => sprite());
 
@@ -70,6 +83,7 @@ const ViewSkin = (props: ViewSkinProps) => {
       sprite: sprite(),
       frame: frame(),
       scale: scaleToNumber(scale()),
+      format: format(),
     };
   };
 
@@ -132,6 +146,20 @@ const ViewSkin = (props: ViewSkinProps) => {
               }}
             </For>
           </select>
+          <select
+            on:change={(ev) => {
+              setFormat(ev.target.value as SpriteFormat);
+            }}
+            value={format()}
+          >
+            <For each={ALLOWED_FORMATS}>
+              {(item, _index) => {
+                return (
+                  <option value={item}>{item.toUpperCase()}</option>
+                );
+              }}
+            </For>
+          </select>
           <button on:click={onResetSpray}>
               { "Use Preferred Spray" }
           </button>
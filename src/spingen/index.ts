@@ -1,7 +1,7 @@
 // All of the internal stuff for spingen.
 import * as Comlink from 'comlink';
 import { createContext } from 'solid-js';
-import { Spray, Skin, SpingenWorker, SkinOptions } from './shared.ts';
+import { Spray, Skin, SpingenWorker, SkinOptions, PortraitKind } from './shared.ts';
 
 // share all types
 export * from './shared.ts';
@@ -55,6 +55,10 @@ export class Spingen {
   createSkinThumbnail(skin: Skin, spray: Spray | null): Promise<string> {
     return this.comlink.createSkinThumbnail(skin, spray);
   }
+
+  createSkinPortrait(skin: Skin, spray: Spray | null, kind: PortraitKind): Promise<string> {
+    return this.comlink.createSkinPortrait(skin, spray, kind);
+  }
 }
 
 // Create a context provider for the spingen
@@ -7,12 +7,18 @@ export interface SpingenWorker {
   createSprayImage: (spray: Spray) => string;
   createSkinAnimation: (skin: Skin, spray: Spray | null, options: SkinOptions) => string;
   createSkinThumbnail: (skin: Skin, spray: Spray | null) => string;
+  createSkinPortrait: (skin: Skin, spray: Spray | null, kind: PortraitKind) => string;
 }
 
+export type PortraitKind = 'rank' | 'wanted' | 'minimap';
+
 export interface SkinOptions {
   sprite: string;
   frame: string;
   scale: number;
+  format: string;
+  // draw at the sprites' own resolution, ignoring highresscale
+  native?: boolean;
 };
 
 export interface Spray {
@@ -21,13 +27,38 @@ export interface Spray {
 };
 
 export interface Sprite {
+  name: string;
+  category: string;
+  animates: boolean;
   frames: string[];
 }
 
+export interface SkinFlags {
+  machine: boolean;
+  ironman: boolean;
+  badnik: boolean;
+}
+
+export interface SkinSound {
+  sound: string;
+  lump: string;
+}
+
 export interface Skin {
   name: string;
   realname: string;
   kartspeed: number;
   kartweight: number;
+  startcolor: number;
+  prefcolor: string;
+  prefoffcolor: string | null;
+  flags: SkinFlags;
+  rivals: string[];
+  facerank: string;
+  facewant: string;
+  facemmap: string;
+  highresscale: number;
+  followers: string[];
+  sounds: SkinSound[];
   sprites: Map<string, Sprite>;
 };
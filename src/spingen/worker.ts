@@ -1,5 +1,5 @@
-import { Spingen, Spray as WasmSpray, Skin as WasmSkin, GifOptions } from '../../spingen-lib/pkg/spingen';
-import { SpingenWorker, Spray, Skin, SprayFn, SkinFn, SkinOptions } from './shared.ts';
+import { Spingen, Spray as WasmSpray, Skin as WasmSkin, SkinImage, GifOptions, AnimationFormat } from '../../spingen-lib/pkg/spingen';
+import { SpingenWorker, Spray, Skin, SprayFn, SkinFn, SkinOptions, PortraitKind } from './shared.ts';
 import * as Comlink from 'comlink';
 
 // Create a new Spingen instance to communicate to our image algorithms.
@@ -28,19 +28,57 @@ async function loadFile(
       realname: skin.realname,
       kartspeed: skin.kartspeed,
       kartweight: skin.kartweight,
+      startcolor: skin.startcolor,
+      prefcolor: skin.prefcolor,
+      prefoffcolor: skin.prefoffcolor ?? null,
+      flags: {
+        machine: skin.machine,
+        ironman: skin.ironman,
+        badnik: skin.badnik,
+      },
+      rivals: skin.rivals,
+      facerank: skin.facerank,
+      facewant: skin.facewant,
+      facemmap: skin.facemmap,
+      highresscale: skin.highresscale,
+      followers: skin.followers,
+      sounds: skin.sounds.map((sound) => {
+        const entry = { sound: sound.sound, lump: sound.lump };
+        sound.free();
+        return entry;
+      }),
       sprites: new Map(
         skin
           .sprites()
           .map((name) => {
-            return [name, {
+            const info = skin.spriteInfo(name);
+            const sprite = {
+              name: info.name,
+              category: info.category,
+              animates: info.animates,
               frames: skin.frames(name),
-            }];
+            };
+            info.free();
+
+            return [name, sprite];
           })
       ),
     });
 
     skin.free();
   });
+
+  // report cross-file problems
+  const report = spingen.loadReport();
+  for (const overridden of report.overridden) {
+    console.log(`${overridden.kind} ${overridden.id} from ${overridden.file ?? 'the base game'} overridden by ${overridden.by}`);
+    overridden.free();
+  }
+  for (const unresolved of report.unresolved) {
+    console.warn(`skin ${unresolved.skin} from ${unresolved.file} has unknown ${unresolved.field} ${unresolved.value}`);
+    unresolved.free();
+  }
+  report.free();
 }
 
 function createSprayImage(spray: Spray) {
@@ -51,19 +89,38 @@ function createSkinAnimation(skin: Skin, spray: Spray | null, options: SkinOptio
   // build options struct
   const gifOptions = new GifOptions();
   gifOptions.scale = options.scale;
+  gifOptions.format = options.format as AnimationFormat;
+  gifOptions.native = options.native ?? false;
 
   // generate image
-  return spingen.generateSkinAnimation(
+  const image = spingen.generateSkinAnimation(
     skin.name,
     spray?.id,
     options.sprite,
     options.frame,
     gifOptions,
   );
+
+  return takeUrl(image);
 }
 
 function createSkinThumbnail(skin: Skin, spray: Spray | null) {
-  return spingen.generateSkinThumbnail(skin.name, spray?.id);
+  return takeUrl(spingen.generateSkinThumbnail(skin.name, spray?.id));
+}
+
+function createSkinPortrait(skin: Skin, spray: Spray | null, kind: PortraitKind) {
+  return spingen.generateSkinPortrait(skin.name, spray?.id, kind, new GifOptions());
+}
+
+function takeUrl(image: SkinImage) {
+  if (image.fallback) {
+    console.log(`skin is missing sprite, falling back to ${image.sprite}`);
+  }
+
+  const url = image.url;
+  image.free();
+
+  return url;
 }
 
 // Create comlink
@@ -72,6 +129,7 @@ const spingenWorker: SpingenWorker = {
   createSprayImage,
   createSkinAnimation,
   createSkinThumbnail,
+  createSkinPortrait,
 };
 
 // Expose comlink